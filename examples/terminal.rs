@@ -0,0 +1,241 @@
+/*
+plays the game in a terminal, for testing the rules without an emulator
+    cargo run --example terminal --target x86_64-unknown-linux-gnu
+pass --relative for relative controls, left and right turn from the way the snake is heading
+pass --size WxH for a different board, like --size 24x12
+
+arrows or wasd to steer, r to restart, q or ctrl+c to quit
+the game is ticked 60 times a second like the console's vsync handler, so the snake moves at the same speed
+*/
+extern crate snake;
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use snake::rng::Rng;
+use snake::snake::{Direction, Event, Game, Location, TickResult, Turn};
+
+enum Key {
+    Steer(Direction),
+    Restart,
+    Quit,
+}
+
+// puts the terminal into raw-ish mode and puts it back when dropped
+struct RawTerminal {
+    saved: String,
+}
+
+impl RawTerminal {
+    fn new() -> RawTerminal {
+        let saved = stty(&["-g"]);
+        stty(&["-icanon", "-echo", "-isig", "min", "1"]);
+        // hide the cursor
+        print!("\x1b[?25l\x1b[2J");
+        RawTerminal {
+            saved: saved.trim().to_owned(),
+        }
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        stty(&[self.saved.as_str()]);
+        println!("\x1b[0m\x1b[?25h");
+        let _ = io::stdout().flush();
+    }
+}
+
+fn stty(args: &[&str]) -> String {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .expect("couldn't run stty");
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+// stdin blocks, so it gets read on its own thread
+fn read_keys() -> Receiver<Key> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut escape = 0;
+        for byte in io::stdin().lock().bytes() {
+            let byte = match byte {
+                Ok(byte) => byte,
+                Err(_) => break,
+            };
+
+            // arrow keys come in as ESC [ A-D
+            let key = match (escape, byte) {
+                (_, 0x1b) => { escape = 1; continue }
+                (1, b'[') => { escape = 2; continue }
+                (2, b'A') => Some(Key::Steer(Direction::Up)),
+                (2, b'B') => Some(Key::Steer(Direction::Down)),
+                (2, b'C') => Some(Key::Steer(Direction::Right)),
+                (2, b'D') => Some(Key::Steer(Direction::Left)),
+                (_, b'w') | (_, b'W') => Some(Key::Steer(Direction::Up)),
+                (_, b's') | (_, b'S') => Some(Key::Steer(Direction::Down)),
+                (_, b'd') | (_, b'D') => Some(Key::Steer(Direction::Right)),
+                (_, b'a') | (_, b'A') => Some(Key::Steer(Direction::Left)),
+                (_, b'r') | (_, b'R') => Some(Key::Restart),
+                (_, b'q') | (_, b'Q') | (_, 3) => Some(Key::Quit),
+                _ => None,
+            };
+            escape = 0;
+
+            if let Some(key) = key {
+                if sender.send(key).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+fn seeds() -> [u8; 2] {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+    [(nanos >> 8) as u8, (nanos >> 16) as u8]
+}
+
+// background colours, each cell is 2 characters wide so the board comes out roughly square
+static EMPTY: &str = "\x1b[48;5;236m  ";
+static HEAD: &str = "\x1b[48;5;120m  ";
+static BODY: &str = "\x1b[48;5;34m  ";
+static FOOD: &str = "\x1b[48;5;236m\x1b[38;5;196m()";
+static PREDICTION: &str = "\x1b[49m\x1b[38;5;28m[]";
+static HEAD_PREDICTION: &str = "\x1b[49m\x1b[38;5;120m[]";
+static OUTSIDE: &str = "\x1b[49m  ";
+
+/*
+the board plus a ring of cells around it for the wrap predictions
+y goes up on the console, so the top row printed is height - 1
+*/
+fn frame(game: &Game, status: &str) -> String {
+    let width = game.width as i32;
+    let height = game.height as i32;
+
+    let mut outside = vec![OUTSIDE; ((width + 2) as usize) * ((height + 2) as usize)];
+    let index = |x: i32, y: i32| ((y + 1) as usize) * ((width + 2) as usize) + ((x + 1) as usize);
+    for x in 0..game.width {
+        for y in 0..game.height {
+            let cell = match game.at(x, y) {
+                Location::Head(_) => HEAD_PREDICTION,
+                Location::Body(_) => PREDICTION,
+                _ => continue,
+            };
+            for (pos, _) in game.wrap_predictions(x, y) {
+                // the head's prediction wins if two cells land on the same corner
+                if outside[index(pos[0], pos[1])] != HEAD_PREDICTION {
+                    outside[index(pos[0], pos[1])] = cell;
+                }
+            }
+        }
+    }
+
+    let mut out = String::from("\x1b[H");
+    for y in (-1..height + 1).rev() {
+        for x in -1..width + 1 {
+            if x < 0 || y < 0 || x >= width || y >= height {
+                out.push_str(outside[index(x, y)]);
+                continue;
+            }
+            out.push_str(match game.at(x as u16, y as u16) {
+                Location::Head(_) => HEAD,
+                Location::Body(_) => BODY,
+                Location::Food => FOOD,
+                Location::Empty => EMPTY,
+            });
+        }
+        out.push_str("\x1b[0m\r\n");
+    }
+    out.push_str(&format!("\x1b[0m\x1b[K size {}  {}\r\n", game.size, status));
+    out.push_str("\x1b[K arrows/wasd steer, r restart, q quit\r\n");
+    out
+}
+
+// WxH after --size, the console's board if it's missing or doesn't parse
+fn board_size() -> [u16; 2] {
+    let args: Vec<String> = env::args().collect();
+    let size = args.iter().position(|arg| arg == "--size").and_then(|i| args.get(i + 1));
+    let parsed = size.and_then(|size| {
+        let mut parts = size.split('x').map(|part| part.parse::<u16>().ok());
+        Some([parts.next()??, parts.next()??])
+    });
+    match parsed {
+        // the snake starts 3 across and 4 down, so anything smaller has nowhere to put it
+        Some([width, height]) if width >= 3 && height >= 4 => [width, height],
+        _ => [12, 12],
+    }
+}
+
+fn main() {
+    let relative = env::args().any(|arg| arg == "--relative");
+
+    let size = board_size();
+    let mut game = Game::with_rng(size[0], size[1], 3, 4, 8, Rng::from_seeds(seeds()));
+
+    let _terminal = RawTerminal::new();
+    let keys = read_keys();
+    let tick_length = Duration::from_secs(1) / 60;
+    let mut next_tick = Instant::now();
+    let mut last_frame = String::new();
+    let mut status = String::new();
+
+    loop {
+        for key in keys.try_iter() {
+            match key {
+                Key::Steer(direction) if relative => match direction {
+                    Direction::Left => game.turn(Turn::Left),
+                    Direction::Right => game.turn(Turn::Right),
+                    _ => {}
+                },
+                Key::Steer(direction) => game.set_direction(direction),
+                Key::Restart => {
+                    game.reset();
+                    status.clear();
+                }
+                Key::Quit => return,
+            }
+        }
+
+        let result = game.tick();
+
+        // no audio on the host, the terminal bell will have to do
+        if game.drain_events().contains(&Event::Death) {
+            print!("\x07");
+        }
+
+        match result {
+            TickResult::Win(msg) => {
+                // same as the console, the game stays on the last frame until it's restarted
+                status = format!("you win! {}", msg);
+            }
+            TickResult::Lose(msg) => {
+                status = format!("last run: {}", msg);
+                game.reset();
+            }
+            TickResult::Continue => {}
+        }
+
+        let frame = frame(&game, &status);
+        if frame != last_frame {
+            print!("{}", frame);
+            let _ = io::stdout().flush();
+            last_frame = frame;
+        }
+
+        next_tick += tick_length;
+        let now = Instant::now();
+        if next_tick > now {
+            thread::sleep(next_tick - now);
+        } else {
+            next_tick = now;
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::f32::consts::PI;
+
+/*
+easing curves
+    all of them take a progress value in [0,1] and return the eased progress
+    most stay in [0,1], back and elastic intentionally overshoot
+*/
+#[derive(Clone, Copy)]
+pub enum Easing {
+    Linear,
+    Step, // jumps to the end value when the keyframe is reached
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicOut,
+    SineInOut,
+    BackOut,
+    ElasticOut,
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::Step => if t >= 1.0 { 1.0 } else { 0.0 },
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Easing::BackOut => {
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+            }
+            Easing::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    let c4 = (2.0 * PI) / 3.0;
+                    2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * c4).sin() + 1.0
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static ALL: [Easing; 9] = [
+        Easing::Linear, Easing::Step, Easing::QuadIn, Easing::QuadOut, Easing::QuadInOut,
+        Easing::CubicOut, Easing::SineInOut, Easing::BackOut, Easing::ElasticOut,
+    ];
+
+    #[test]
+    fn endpoints() {
+        for easing in ALL.iter() {
+            assert!(easing.apply(0.0).abs() < 0.00001);
+            assert!((easing.apply(1.0) - 1.0).abs() < 0.00001);
+            // progress outside [0,1] is clamped
+            assert!(easing.apply(-1.0).abs() < 0.00001);
+            assert!((easing.apply(2.0) - 1.0).abs() < 0.00001);
+        }
+    }
+
+    #[test]
+    fn in_between() {
+        assert!(Easing::Linear.apply(0.25) == 0.25);
+        assert!(Easing::Step.apply(0.99) == 0.0);
+        assert!(Easing::QuadIn.apply(0.5) < 0.5 && Easing::QuadOut.apply(0.5) > 0.5);
+        assert!((Easing::QuadInOut.apply(0.5) - 0.5).abs() < 0.00001);
+        // back overshoots on the way in
+        assert!((0..100).any(|i| Easing::BackOut.apply(i as f32 / 100.0) > 1.0));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod easing;
+pub mod track;
+pub mod timeline;
+pub mod presets;
@@ -0,0 +1,45 @@
+use crate::util::{vec3, vec3_from, vec4};
+
+use super::easing::Easing;
+use super::timeline::Animation;
+use super::track::Track;
+
+// food grows in from nothing, overshoots, then settles
+pub fn food_spawn() -> Animation {
+    Animation::new()
+        .scale(Track::new(vec3_from(0.0))
+            .key(12, vec3_from(1.25), Easing::BackOut)
+            .key(24, vec3_from(1.0), Easing::QuadInOut))
+}
+
+// quick swell and flash on the cell the food was eaten in
+pub fn food_eaten() -> Animation {
+    Animation::new()
+        .scale(Track::new(vec3_from(1.0))
+            .key(4, vec3(1.4, 1.4, 1.6), Easing::QuadOut)
+            .key(18, vec3_from(1.0), Easing::ElasticOut))
+        .color(Track::new(vec4(1.0, 1.0, 1.0, 1.0))
+            .key(3, vec4(2.0, 2.0, 2.0, 1.0), Easing::QuadOut)
+            .key(15, vec4(1.0, 1.0, 1.0, 1.0), Easing::QuadIn))
+}
+
+// camera jolt that dies down, offsets are in world units
+pub fn death_shake() -> Animation {
+    Animation::new()
+        .position(Track::new(vec3_from(0.0))
+            .key(3, vec3(1.2, -0.8, 0.4), Easing::QuadOut)
+            .key(6, vec3(-1.0, 0.9, -0.3), Easing::SineInOut)
+            .key(10, vec3(0.7, 0.5, 0.2), Easing::SineInOut)
+            .key(14, vec3(-0.5, -0.4, -0.1), Easing::SineInOut)
+            .key(19, vec3(0.25, 0.2, 0.0), Easing::SineInOut)
+            .key(26, vec3_from(0.0), Easing::QuadOut))
+}
+
+// whole board fades and grows in when a round starts
+pub fn level_transition() -> Animation {
+    Animation::new()
+        .scale(Track::new(vec3(0.7, 0.7, 0.0))
+            .key(40, vec3_from(1.0), Easing::CubicOut))
+        .color(Track::new(vec4(0.0, 0.0, 0.0, 1.0))
+            .key(30, vec4(1.0, 1.0, 1.0, 1.0), Easing::QuadOut))
+}
@@ -0,0 +1,235 @@
+use dbsdk_rs::{math::{Vector3, Vector4}, vdp};
+
+use crate::util::{vec3_from, vec4};
+
+use super::track::Track;
+
+/*
+an animation is a set of keyframed tracks that are layered on top of whatever is drawn
+    position is an offset, in cells for cell targets and world units otherwise
+    scale is a multiplier applied around the target's origin
+    color is multiplied with the vertex colors
+*/
+#[derive(Clone)]
+pub struct Animation {
+    pub position: Track<Vector3>,
+    pub scale: Track<Vector3>,
+    pub color: Track<Vector4>,
+    pub looping: bool,
+}
+
+impl Default for Animation {
+    fn default() -> Animation {
+        Animation::new()
+    }
+}
+
+impl Animation {
+    pub fn new() -> Animation {
+        Animation {
+            position: Track::new(Vector3::zero()),
+            scale: Track::new(vec3_from(1.0)),
+            color: Track::new(vec4(1.0, 1.0, 1.0, 1.0)),
+            looping: false,
+        }
+    }
+
+    pub fn position(mut self, track: Track<Vector3>) -> Animation {
+        self.position = track;
+        self
+    }
+
+    pub fn scale(mut self, track: Track<Vector3>) -> Animation {
+        self.scale = track;
+        self
+    }
+
+    pub fn color(mut self, track: Track<Vector4>) -> Animation {
+        self.color = track;
+        self
+    }
+
+    pub fn looping(mut self, looping: bool) -> Animation {
+        self.looping = looping;
+        self
+    }
+
+    pub fn duration(&self) -> u32 {
+        let mut duration = self.position.duration();
+        if self.scale.duration() > duration {
+            duration = self.scale.duration();
+        }
+        if self.color.duration() > duration {
+            duration = self.color.duration();
+        }
+        duration
+    }
+
+    pub fn sample(&self, tick: u32) -> AnimationSample {
+        let duration = self.duration();
+        let tick = if self.looping && duration > 0 { tick % duration } else { tick } as f32;
+
+        AnimationSample {
+            position: self.position.sample(tick),
+            scale: self.scale.sample(tick),
+            color: self.color.sample(tick),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct AnimationSample {
+    pub position: Vector3,
+    pub scale: Vector3,
+    pub color: Vector4,
+}
+
+impl AnimationSample {
+    pub fn identity() -> AnimationSample {
+        AnimationSample {
+            position: Vector3::zero(),
+            scale: vec3_from(1.0),
+            color: vec4(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+
+    // layer another sample on top of this one
+    pub fn combine(&self, other: AnimationSample) -> AnimationSample {
+        AnimationSample {
+            position: self.position + other.position,
+            scale: self.scale * other.scale,
+            color: self.color * other.color,
+        }
+    }
+
+    // scale around origin, then offset by position * unit
+    pub fn apply(&self, verts: &mut [vdp::Vertex], origin: Vector3, unit: f32) {
+        let offset = self.position * unit;
+
+        for vert in verts.iter_mut() {
+            vert.position.x = origin.x + (vert.position.x - origin.x) * self.scale.x + offset.x;
+            vert.position.y = origin.y + (vert.position.y - origin.y) * self.scale.y + offset.y;
+            vert.position.z = origin.z + (vert.position.z - origin.z) * self.scale.z + offset.z;
+            vert.color = vert.color * self.color;
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Target {
+    Cell(u16, u16),
+    Camera,
+    Board,
+}
+
+#[derive(Clone)]
+struct Playing {
+    target: Target,
+    animation: Animation,
+    start: u32,
+}
+
+// keeps track of every running animation, ticked once per vsync
+pub struct Timeline {
+    tick: u32,
+    playing: Vec<Playing>,
+}
+
+impl Default for Timeline {
+    fn default() -> Timeline {
+        Timeline::new()
+    }
+}
+
+impl Timeline {
+    pub fn new() -> Timeline {
+        Timeline {
+            tick: 0,
+            playing: Vec::new(),
+        }
+    }
+
+    pub fn play(&mut self, target: Target, animation: Animation) {
+        self.playing.push(Playing {
+            target,
+            animation,
+            start: self.tick,
+        });
+    }
+
+    pub fn stop(&mut self, target: Target) {
+        self.playing.retain(|p| p.target != target);
+    }
+
+    pub fn tick(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+
+        let tick = self.tick;
+        self.playing.retain(|p| p.animation.looping || tick.wrapping_sub(p.start) <= p.animation.duration());
+    }
+
+    pub fn is_playing(&self, target: Target) -> bool {
+        self.playing.iter().any(|p| p.target == target)
+    }
+
+    // every animation on the same target is layered together
+    pub fn sample(&self, target: Target) -> AnimationSample {
+        let mut sample = AnimationSample::identity();
+        for p in self.playing.iter() {
+            if p.target == target {
+                sample = sample.combine(p.animation.sample(self.tick.wrapping_sub(p.start)));
+            }
+        }
+        sample
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::easing::Easing;
+
+    fn bump(length: u32) -> Animation {
+        Animation::new().position(Track::new(Vector3::zero()).key(length, vec3_from(1.0), Easing::Linear))
+    }
+
+    #[test]
+    fn finished_animations_leave() {
+        let mut timeline = Timeline::new();
+        timeline.play(Target::Board, bump(3));
+        timeline.play(Target::Camera, bump(6));
+        timeline.play(Target::Cell(1, 2), bump(1).looping(true));
+
+        for _ in 0..3 {
+            timeline.tick();
+        }
+        // the last tick is still sampled
+        assert!(timeline.is_playing(Target::Board));
+        assert!(timeline.sample(Target::Board).position.x == 1.0);
+
+        timeline.tick();
+        assert!(!timeline.is_playing(Target::Board));
+        assert!(timeline.sample(Target::Board).position.x == 0.0);
+        assert!(timeline.is_playing(Target::Camera));
+
+        for _ in 0..10 {
+            timeline.tick();
+        }
+        assert!(!timeline.is_playing(Target::Camera));
+        // looping ones stay until they're stopped
+        assert!(timeline.is_playing(Target::Cell(1, 2)));
+        timeline.stop(Target::Cell(1, 2));
+        assert!(!timeline.is_playing(Target::Cell(1, 2)));
+    }
+
+    #[test]
+    fn same_target_layers() {
+        let mut timeline = Timeline::new();
+        timeline.play(Target::Board, bump(4));
+        timeline.play(Target::Board, bump(4));
+        timeline.tick();
+        timeline.tick();
+        assert!(timeline.sample(Target::Board).position.x == 1.0);
+        assert!(timeline.sample(Target::Camera).position.x == 0.0);
+    }
+}
@@ -0,0 +1,117 @@
+use dbsdk_rs::math::{Vector3, Vector4};
+
+use super::easing::Easing;
+
+pub trait Lerp: Copy {
+    fn lerp(from: Self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(from: f32, to: f32, t: f32) -> f32 {
+        from + (to - from) * t
+    }
+}
+
+impl Lerp for Vector3 {
+    fn lerp(from: Vector3, to: Vector3, t: f32) -> Vector3 {
+        from + (to - from) * t
+    }
+}
+
+impl Lerp for Vector4 {
+    fn lerp(from: Vector4, to: Vector4, t: f32) -> Vector4 {
+        from + (to - from) * t
+    }
+}
+
+// easing is the curve used to travel from the previous keyframe into this one
+#[derive(Clone, Copy)]
+pub struct Keyframe<T: Lerp> {
+    pub tick: u32,
+    pub value: T,
+    pub easing: Easing,
+}
+
+#[derive(Clone)]
+pub struct Track<T: Lerp> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Lerp> Track<T> {
+    // a track always starts with a keyframe at tick 0, a track with only that keyframe is constant
+    pub fn new(value: T) -> Track<T> {
+        Track {
+            keyframes: vec![Keyframe { tick: 0, value, easing: Easing::Linear }],
+        }
+    }
+
+    // keyframes must be added in order
+    pub fn key(mut self, tick: u32, value: T, easing: Easing) -> Track<T> {
+        let last = self.duration();
+        let tick = if tick < last { last } else { tick };
+        self.keyframes.push(Keyframe { tick, value, easing });
+        self
+    }
+
+    pub fn duration(&self) -> u32 {
+        self.keyframes[self.keyframes.len() - 1].tick
+    }
+
+    pub fn sample(&self, tick: f32) -> T {
+        let first = self.keyframes[0];
+        if tick <= first.tick as f32 {
+            return first.value
+        }
+
+        for i in 1..self.keyframes.len() {
+            let from = self.keyframes[i - 1];
+            let to = self.keyframes[i];
+            if tick < to.tick as f32 {
+                let length = (to.tick - from.tick) as f32;
+                let progress = (tick - from.tick as f32) / length;
+                return T::lerp(from.value, to.value, to.easing.apply(progress))
+            }
+        }
+
+        self.keyframes[self.keyframes.len() - 1].value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track() -> Track<f32> {
+        Track::new(0.0)
+            .key(10, 1.0, Easing::Linear)
+            .key(20, 3.0, Easing::Step)
+    }
+
+    #[test]
+    fn between_keyframes() {
+        let track = track();
+        assert!(track.duration() == 20);
+        assert!(track.sample(0.0) == 0.0);
+        assert!(track.sample(5.0) == 0.5);
+        assert!(track.sample(10.0) == 1.0);
+        // step holds the previous value until its keyframe
+        assert!(track.sample(19.5) == 1.0);
+        assert!(track.sample(20.0) == 3.0);
+    }
+
+    #[test]
+    fn before_and_past_the_end() {
+        let track = track();
+        assert!(track.sample(-5.0) == 0.0);
+        assert!(track.sample(21.0) == 3.0);
+        assert!(track.sample(1_000_000.0) == 3.0);
+        assert!(Track::new(2.0).sample(50.0) == 2.0);
+    }
+
+    #[test]
+    fn keyframes_out_of_order_are_pushed_back() {
+        let track = Track::new(0.0).key(10, 1.0, Easing::Linear).key(5, 2.0, Easing::Linear);
+        assert!(track.duration() == 10);
+        assert!(track.sample(10.0) == 2.0);
+    }
+}
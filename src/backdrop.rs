@@ -0,0 +1,247 @@
+use std::f32::consts::PI;
+
+use dbsdk_rs::{math::{Matrix4x4, Vector3, Vector4}, vdp};
+
+use crate::animation::track::Lerp;
+use crate::draw::ASPECT;
+use crate::geometry::cube::Cube;
+use crate::geometry::floaty::{FloatyCameraOffsets, StateFloaty};
+use crate::geometry::square::Square;
+use crate::geometry::weight::CENTER;
+use crate::rng::Rng;
+use crate::theme;
+use crate::util::{vec3, vec3_from, quat_euler};
+
+/*
+nebula colors go from the edge of a cloud to its middle
+stars twinkle between star and half of it
+*/
+#[derive(Clone, Copy)]
+pub struct BackdropPalette {
+    pub star: Vector4,
+    pub nebula: [Vector4; 2],
+    pub debris: Vector4,
+}
+
+impl BackdropPalette {
+    pub fn classic() -> BackdropPalette {
+        theme::CLASSIC.backdrop
+    }
+}
+
+// always the same sky, it doesn't use the game's rng so it can't change where food lands
+static SEEDS: [u8; 2] = [91, 17];
+
+static STARS: usize = 160;
+static NEBULAE: usize = 5;
+static DEBRIS: usize = 10;
+
+/*
+distances from the camera in world units, the board is never further than about 60 so everything here stays behind it
+    the far end has to be inside the projection's far plane
+*/
+static STAR_DEPTH: [f32; 2] = [150.0, 190.0];
+static NEBULA_DEPTH: [f32; 2] = [192.0, 198.0];
+static DEBRIS_DEPTH: [f32; 2] = [90.0, 140.0];
+
+// half the screen's height at a depth of 1, for the projection's 1 radian field of view
+static VIEW_HALF_HEIGHT: f32 = 0.546;
+// how far past the edges of the screen things are placed, so the camera drifting doesn't show an empty edge
+static MARGIN: f32 = 1.3;
+
+// how much of the floaty camera's turning the backdrop follows, the full amount swings the sky around a lot
+static CAMERA_ROTATION: f32 = 0.3;
+
+// nebulae drift sideways this many world units each vsync tick and wrap round
+static NEBULA_SPEED: f32 = 0.004;
+// layers in each cloud, there's no blending so it's nested squares from the edge color in
+static NEBULA_LAYERS: usize = 6;
+
+struct Star {
+    position: Vector3,
+    size: f32,
+    twinkle_ticks: f32,
+    twinkle_offset: f32,
+}
+
+struct Nebula {
+    position: Vector3,
+    size: f32,
+    angle: f32, // degrees around the view direction
+}
+
+struct Debris {
+    position: Vector3,
+    size: f32,
+    spin: Vector3, // degrees per vsync tick around each axis
+    floaty: StateFloaty,
+}
+
+/*
+stars, nebulae and debris floating behind the board
+    positions are relative to the camera, x right, y up and looking down -z
+    it gets its own camera that only follows the floaty camera, so it stays put however the board is framed
+*/
+pub struct Backdrop {
+    stars: Vec<Star>,
+    nebulae: Vec<Nebula>,
+    debris: Vec<Debris>,
+    pub palette: BackdropPalette,
+}
+
+impl Default for Backdrop {
+    fn default() -> Backdrop {
+        Backdrop::new()
+    }
+}
+
+impl Backdrop {
+    pub fn new() -> Backdrop {
+        let mut rng = Rng::from_seeds(SEEDS);
+
+        let stars = (0..STARS).map(|_| Star {
+            position: spot(&mut rng, STAR_DEPTH),
+            size: 0.25 + rng.random_f32() * 0.35,
+            twinkle_ticks: 60.0 + rng.random_f32() * 240.0,
+            twinkle_offset: rng.random_f32(),
+        }).collect();
+
+        let nebulae = (0..NEBULAE).map(|_| Nebula {
+            position: spot(&mut rng, NEBULA_DEPTH),
+            size: 30.0 + rng.random_f32() * 40.0,
+            angle: rng.random_f32() * 90.0,
+        }).collect();
+
+        let debris = (0..DEBRIS).map(|_| Debris {
+            position: spot(&mut rng, DEBRIS_DEPTH),
+            size: 3.0 + rng.random_f32() * 4.0,
+            spin: vec3(rng.random_f32() - 0.5, rng.random_f32() - 0.5, rng.random_f32() - 0.5),
+            floaty: StateFloaty::new(
+                vec3(600.0 + rng.random_f32() * 600.0, 600.0 + rng.random_f32() * 600.0, 600.0 + rng.random_f32() * 600.0),
+                vec3(rng.random_f32() * 600.0, rng.random_f32() * 600.0, rng.random_f32() * 600.0),
+                vec3(2.0, 2.0, 1.0),
+            ),
+        }).collect();
+
+        Backdrop {
+            stars,
+            nebulae,
+            debris,
+            palette: BackdropPalette::classic(),
+        }
+    }
+
+    // floatiness scales the debris drift like Game::floatiness
+    pub fn tris(&self, tick: u32, floatiness: f32) -> Vec<vdp::Vertex> {
+        let mut tris = Vec::new();
+
+        for nebula in self.nebulae.iter() {
+            // wrap across the width things are placed over at that depth
+            let width = -nebula.position.z * VIEW_HALF_HEIGHT * ASPECT * MARGIN;
+            let x = (nebula.position.x + cycle(tick, NEBULA_SPEED, width * 2.0) + width).rem_euclid(width * 2.0) - width;
+            let center = vec3(x, nebula.position.y, nebula.position.z);
+            for layer in 0..NEBULA_LAYERS {
+                let t = layer as f32 / (NEBULA_LAYERS - 1) as f32;
+                let color = Vector4::lerp(self.palette.nebula[0], self.palette.nebula[1], t);
+                // inner layers sit a little closer so they're drawn over the outer ones
+                let layer_center = center + vec3(0.0, 0.0, layer as f32 * 0.5);
+                let half = nebula.size * (1.0 - t * 0.7) / 2.0;
+                let angle = nebula.angle + layer as f32 * 15.0;
+                tris.append(&mut turned_square(layer_center, half, angle, color));
+            }
+        }
+
+        for star in self.stars.iter() {
+            let phase = (cycle(tick, 1.0, star.twinkle_ticks) / star.twinkle_ticks + star.twinkle_offset) * 2.0 * PI;
+            let brightness = 0.75 + phase.sin() * 0.25;
+            let half = star.size / 2.0;
+            let from = star.position - vec3(half, half, 0.0);
+            let to = star.position + vec3(half, half, 0.0);
+            let color = self.palette.star * Vector4::new(brightness, brightness, brightness, 1.0);
+            tris.append(&mut Square::new(from, to, vec3_from(1.0), color, CENTER).tris());
+        }
+
+        for debris in self.debris.iter() {
+            let half = debris.size / 2.0;
+            let cube = Cube::new(vec3_from(-half), vec3_from(half), vec3_from(1.0), self.palette.debris, CENTER);
+            let angles = vec3(cycle(tick, debris.spin.x, 360.0), cycle(tick, debris.spin.y, 360.0), cycle(tick, debris.spin.z, 360.0));
+            let spin = Matrix4x4::rotation(quat_euler(angles));
+            let mut verts = cube.tris();
+            for vert in verts.iter_mut() {
+                let p = spin * vert.position;
+                vert.position = Vector4::new(p.x + debris.position.x, p.y + debris.position.y, p.z + debris.position.z, 1.0);
+            }
+            tris.append(&mut debris.floaty.scaled(floatiness).float(verts, debris.size, tick));
+        }
+
+        tris
+    }
+}
+
+// rotation, position, projection, a toned down version of what the board's camera does with the floaty offsets
+pub fn camera_matrices(camera_offset: &FloatyCameraOffsets) -> [Matrix4x4; 3] {
+    let rotation = Matrix4x4::rotation(quat_euler(camera_offset.rotation * CAMERA_ROTATION));
+    let position = Matrix4x4::translation(camera_offset.translation);
+    let projection = Matrix4x4::projection_perspective(ASPECT, 1.0, 0.1, 200.0);
+
+    [rotation, position, projection]
+}
+
+// how far into a repeating length something moving rate a tick has got, in f64 like floaty's phase so it keeps moving smoothly after days
+fn cycle(tick: u32, rate: f32, length: f32) -> f32 {
+    (tick as f64 * rate as f64).rem_euclid(length as f64) as f32
+}
+
+// somewhere on screen, or just off it, at a random depth in the range
+fn spot(rng: &mut Rng, depth: [f32; 2]) -> Vector3 {
+    let z = depth[0] + rng.random_f32() * (depth[1] - depth[0]);
+    let half_height = z * VIEW_HALF_HEIGHT * MARGIN;
+    let x = (rng.random_f32() * 2.0 - 1.0) * half_height * ASPECT;
+    let y = (rng.random_f32() * 2.0 - 1.0) * half_height;
+    vec3(x, y, -z)
+}
+
+// a square facing the camera, turned by angle degrees
+fn turned_square(center: Vector3, half: f32, angle: f32, color: Vector4) -> Vec<vdp::Vertex> {
+    let from = vec3(-half, -half, 0.0);
+    let to = vec3(half, half, 0.0);
+    let turn = Matrix4x4::rotation(quat_euler(vec3(0.0, 0.0, angle)));
+    let mut verts = Square::new(from, to, vec3_from(1.0), color, CENTER).tris();
+    for vert in verts.iter_mut() {
+        let p = turn * vert.position;
+        vert.position = Vector4::new(p.x + center.x, p.y + center.y, p.z + center.z, 1.0);
+    }
+    verts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn same(a: &[vdp::Vertex], b: &[vdp::Vertex]) -> bool {
+        a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| {
+            a.position.x == b.position.x && a.position.y == b.position.y && a.position.z == b.position.z
+                && a.color.x == b.color.x && a.color.y == b.color.y && a.color.z == b.color.z
+        })
+    }
+
+    // past 2^24 an f32 can't tell neighbouring ticks apart, the sky should still move every one of them
+    // no floatiness so it's only the twinkle, drift and spin
+    #[test]
+    fn still_moves_after_days() {
+        let backdrop = Backdrop::new();
+        for &tick in [0, 1 << 20, 1 << 26, u32::MAX - 1].iter() {
+            assert!(!same(&backdrop.tris(tick, 0.0), &backdrop.tris(tick + 1, 0.0)), "stuck at {}", tick);
+            assert!(same(&backdrop.tris(tick, 0.0), &backdrop.tris(tick, 0.0)));
+        }
+    }
+
+    #[test]
+    fn cycles_wrap() {
+        assert!(cycle(0, 0.5, 360.0) == 0.0);
+        assert!(cycle(720, 0.5, 360.0) == 0.0);
+        assert!(cycle(721, 0.5, 360.0) == 0.5);
+        assert!(cycle(3, -1.0, 360.0) == 357.0);
+        assert!((cycle(u32::MAX, 1.0, 60.0) - (u32::MAX % 60) as f32).abs() < 0.001);
+    }
+}
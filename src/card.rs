@@ -0,0 +1,107 @@
+use std::io::{Read, Write};
+
+use dbsdk_rs::io::{self, FileMode, FileStream, IOError};
+
+/*
+files on the memory card in slot A
+    files are allocated in 512 byte blocks the first time they're written, with the icon below
+    reads and writes are whole files, everything we save is small
+*/
+pub static DEVICE: &str = "ma";
+pub static BLOCK_SIZE: usize = 512;
+
+// 16x16 at 4 bits a pixel, a snake chasing a piece of food
+static ICON: [u8; 128] = icon();
+// 0 black, 1 dark green, 2 green, 3 red, as 16 bit RGB555
+static ICON_PALETTE: [u16; 16] = [
+    0x0000, 0x0180, 0x03e0, 0x7c00, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+const fn icon() -> [u8; 128] {
+    let rows: [&[u8; 16]; 16] = [
+        b"................",
+        b"................",
+        b"..2222222.......",
+        b"..2111112.......",
+        b"..2122222.......",
+        b"..212...........",
+        b"..2122222222....",
+        b"..2111111112....",
+        b"..2222222212....",
+        b".........212....",
+        b"...3.....212....",
+        b"..333....212....",
+        b"...3.....212....",
+        b"..22222222212...",
+        b"..22222222222...",
+        b"................",
+    ];
+
+    let mut icon = [0; 128];
+    let mut i = 0;
+    while i < 256 {
+        let pixel = match rows[i / 16][i % 16] {
+            b'1' => 1,
+            b'2' => 2,
+            b'3' => 3,
+            _ => 0,
+        };
+        // two pixels a byte, the first in the high nibble
+        icon[i / 2] |= if i % 2 == 0 { pixel << 4 } else { pixel };
+        i += 1;
+    }
+    icon
+}
+
+pub fn path(name: &str) -> String {
+    format!("/{}/{}", DEVICE, name)
+}
+
+pub fn available() -> bool {
+    io::device_exists(DEVICE)
+}
+
+// None if there's no card or no file, a partial read counts as no file
+pub fn read(name: &str) -> Option<Vec<u8>> {
+    if !available() || !io::file_exists(&path(name)) {
+        return None
+    }
+    let mut file = FileStream::open(&path(name), FileMode::Read).ok()?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).ok()?;
+    Some(data)
+}
+
+// data past the end of the blocks allocated the first time is an error, so size the first write generously
+pub fn write(name: &str, data: &[u8], blocks: i32) -> Result<(), IOError> {
+    if !available() {
+        return Err(IOError::NoSuchDevice)
+    }
+    let path = path(name);
+    let mut file = if io::file_exists(&path) {
+        FileStream::open(&path, FileMode::Write)?
+    } else {
+        FileStream::allocate_memory_card(&path, &ICON, &ICON_PALETTE, blocks)?
+    };
+    file.write_all(data).map_err(|_| IOError::FileTooBig)
+}
+
+// whole file reads and writes, so saving can be tested without a card
+pub trait Storage {
+    fn read(&self, name: &str) -> Option<Vec<u8>>;
+    fn write(&mut self, name: &str, data: &[u8], blocks: i32) -> Result<(), IOError>;
+}
+
+// the card in slot A, through read and write above
+pub struct MemoryCard;
+
+impl Storage for MemoryCard {
+    fn read(&self, name: &str) -> Option<Vec<u8>> {
+        read(name)
+    }
+
+    fn write(&mut self, name: &str, data: &[u8], blocks: i32) -> Result<(), IOError> {
+        write(name, data, blocks)
+    }
+}
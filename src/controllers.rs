@@ -0,0 +1,197 @@
+use dbsdk_rs::gamepad::GamepadState;
+
+use settings::PLAYERS;
+
+// what's behind a gamepad slot
+#[derive(Clone, Copy, PartialEq)]
+pub enum Device {
+    None,
+    Gamepad,
+    // keyboards never show up as connected, a slot becomes a keyboard the first time it sends input while nothing is connected
+    Keyboard,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ControllerEvent {
+    Connected(usize),
+    Disconnected(usize),
+    // the player was given this slot, after starting up or after their controller went away
+    Assigned(usize),
+}
+
+// how far a stick has to move before it counts as someone using the slot, well clear of stick drift
+static ACTIVITY_THRESHOLD: i16 = i16::MAX / 2;
+
+pub fn has_input(state: &GamepadState) -> bool {
+    *state.button_mask != 0
+        || [state.left_stick_x, state.left_stick_y, state.right_stick_x, state.right_stick_y]
+            .iter().any(|v| v.saturating_abs() > ACTIVITY_THRESHOLD)
+}
+
+/*
+keeps track of what's plugged in and which slot the player is using
+    call update once a frame with whether each slot is connected and what it's reading
+    until a slot is assigned the player reads from slot A, like the game always did
+    waiting is set when the player's gamepad is unplugged, the game should pause until a slot gets assigned again
+*/
+pub struct Controllers {
+    pub devices: [Device; PLAYERS],
+    pub player: Option<usize>,
+    pub waiting: bool,
+}
+
+impl Default for Controllers {
+    fn default() -> Controllers {
+        Controllers::new()
+    }
+}
+
+impl Controllers {
+    pub fn new() -> Controllers {
+        Controllers {
+            devices: [Device::None; PLAYERS],
+            player: None,
+            waiting: false,
+        }
+    }
+
+    pub fn player_slot(&self) -> usize {
+        self.player.unwrap_or(0)
+    }
+
+    pub fn update(&mut self, connected: [bool; PLAYERS], states: &[GamepadState; PLAYERS]) -> Vec<ControllerEvent> {
+        let mut events = Vec::new();
+
+        for slot in 0..PLAYERS {
+            let was = self.devices[slot];
+            let now = if connected[slot] {
+                Device::Gamepad
+            } else if was == Device::Keyboard || has_input(&states[slot]) {
+                Device::Keyboard
+            } else {
+                Device::None
+            };
+
+            if was == Device::Gamepad && now != Device::Gamepad {
+                events.push(ControllerEvent::Disconnected(slot));
+            }
+            if was == Device::None && now != Device::None {
+                events.push(ControllerEvent::Connected(slot));
+            }
+            self.devices[slot] = now;
+        }
+
+        if let Some(player) = self.player {
+            if self.devices[player] == Device::None {
+                self.player = None;
+                self.waiting = true;
+            }
+        }
+
+        /*
+        a slot someone is pressing buttons on wins over one that's just plugged in
+            after a disconnect only a slot sending input is taken, so the game doesn't carry on with another pad that happens to be plugged in
+        */
+        if self.player.is_none() {
+            let pressed = (0..PLAYERS).find(|&slot| self.devices[slot] != Device::None && has_input(&states[slot]));
+            let plugged = (0..PLAYERS).find(|&slot| self.devices[slot] == Device::Gamepad);
+            let slot = if self.waiting { pressed } else { pressed.or(plugged) };
+            if let Some(slot) = slot {
+                self.player = Some(slot);
+                self.waiting = false;
+                events.push(ControllerEvent::Assigned(slot));
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dbsdk_rs::gamepad::GamepadButton;
+    use input::empty_state;
+
+    static NONE: [bool; PLAYERS] = [false; PLAYERS];
+
+    fn idle() -> [GamepadState; PLAYERS] {
+        [empty_state(); PLAYERS]
+    }
+
+    // A held down on the given slots
+    fn pressing(slots: &[usize]) -> [GamepadState; PLAYERS] {
+        let mut states = idle();
+        for &slot in slots {
+            states[slot].button_mask.set(GamepadButton::A);
+        }
+        states
+    }
+
+    fn plugged(slots: &[usize]) -> [bool; PLAYERS] {
+        let mut connected = NONE;
+        for &slot in slots {
+            connected[slot] = true;
+        }
+        connected
+    }
+
+    #[test]
+    fn disconnect_and_reconnect() {
+        let mut controllers = Controllers::new();
+        let events = controllers.update(plugged(&[1]), &idle());
+        assert!(events == [ControllerEvent::Connected(1), ControllerEvent::Assigned(1)]);
+        assert!(controllers.player_slot() == 1 && !controllers.waiting);
+
+        // unplugged, the game waits and keeps waiting while nothing happens
+        let events = controllers.update(NONE, &idle());
+        assert!(events == [ControllerEvent::Disconnected(1)]);
+        assert!(controllers.waiting && controllers.player.is_none());
+        assert!(controllers.update(NONE, &idle()).is_empty());
+        assert!(controllers.waiting);
+
+        // plugging back in isn't enough, it has to be used
+        let events = controllers.update(plugged(&[1]), &idle());
+        assert!(events == [ControllerEvent::Connected(1)]);
+        assert!(controllers.waiting);
+        let events = controllers.update(plugged(&[1]), &pressing(&[1]));
+        assert!(events == [ControllerEvent::Assigned(1)]);
+        assert!(controllers.player_slot() == 1 && !controllers.waiting);
+    }
+
+    #[test]
+    fn only_the_slot_sending_input_is_taken() {
+        let mut controllers = Controllers::new();
+        controllers.update(plugged(&[0, 2]), &idle());
+        assert!(controllers.player_slot() == 0);
+
+        // the other pad is still plugged in but the player picks up a third one
+        controllers.update(plugged(&[2]), &idle());
+        assert!(controllers.waiting);
+        controllers.update(plugged(&[2, 3]), &idle());
+        assert!(controllers.waiting);
+        let events = controllers.update(plugged(&[2, 3]), &pressing(&[3]));
+        assert!(events == [ControllerEvent::Assigned(3)]);
+        assert!(controllers.player_slot() == 3);
+
+        // once someone has it, input on another slot doesn't take it away
+        assert!(controllers.update(plugged(&[2, 3]), &pressing(&[2])).is_empty());
+        assert!(controllers.player_slot() == 3);
+    }
+
+    #[test]
+    fn keyboard_claims_a_slot() {
+        let mut controllers = Controllers::new();
+        assert!(controllers.update(NONE, &idle()).is_empty());
+        assert!(controllers.player.is_none() && controllers.player_slot() == 0);
+
+        let events = controllers.update(NONE, &pressing(&[2]));
+        assert!(events == [ControllerEvent::Connected(2), ControllerEvent::Assigned(2)]);
+        assert!(controllers.devices[2] == Device::Keyboard);
+
+        // keyboards never report being connected, letting go of the keys doesn't lose it
+        assert!(controllers.update(NONE, &idle()).is_empty());
+        assert!(controllers.devices[2] == Device::Keyboard);
+        assert!(controllers.player_slot() == 2 && !controllers.waiting);
+    }
+}
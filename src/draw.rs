@@ -1,35 +1,80 @@
 extern crate dbsdk_rs;
 
-use dbsdk_rs::field_offset::offset_of;
 use dbsdk_rs::vdp;
-use dbsdk_rs::math::{Vector4, Matrix4x4, Vector3, Quaternion};
+use dbsdk_rs::math::{Vector4, Matrix4x4, Vector3};
 
 use geometry::cube::Cube;
 
-use crate::geometry::body_cube::BodyCube;
 use crate::geometry::floaty::{StateFloaty, FloatyCameraOffsets};
 use crate::geometry::square::Square;
+use crate::geometry::tube::Tube;
 use crate::geometry::weight::{CENTER, self};
-use crate::util::{vec3_from, vec3};
-
-pub fn transform_draw_tris(tris: &mut Vec<vdp::Vertex>, camera_offset: FloatyCameraOffsets) {
-    Matrix4x4::load_identity_simd();
+use crate::render::Renderer;
+use crate::snake::Direction;
+use crate::theme::Theme;
+use crate::util::{vec3_from, vec3, quat_euler};
+
+// resting camera orientation in degrees, the floaty camera drifts around this
+pub static CAMERA_ANGLE: Vector3 = Vector3::new(-22.6, 0.0, 0.0);
+
+// where the camera looks from
+#[derive(Clone, Copy, PartialEq)]
+pub enum CameraMode {
+    Fixed, // the whole board, from the same angle the game always had
+    Chase, // low behind the head, turning with it so the snake always heads up the screen
+}
 
-    let rotation = Matrix4x4::rotation(Quaternion::new(-0.2 + camera_offset.rotation.x, camera_offset.rotation.y, camera_offset.rotation.z, 1.0));
-    Matrix4x4::mul_simd(&rotation);
+// chase camera tilt in degrees, steeper than the fixed camera since it's closer to the board
+pub static CHASE_ANGLE: Vector3 = Vector3::new(-48.0, 0.0, 0.0);
+
+// the screen's width over its height
+pub static ASPECT: f32 = 640.0 / 480.0;
+
+/*
+moves and scales a board so the fixed camera frames it, extent is how big the board is in board units
+    the camera was set up for a unit square, a square board is left exactly where it is
+    anything else is centered where the square was and grown until it meets the sides or the top and bottom of the screen
+*/
+pub fn board_framing(extent: [f32; 2]) -> Matrix4x4 {
+    let fit = 1.0 / (extent[0] / ASPECT).max(extent[1]);
+    let mut framing = Matrix4x4::scale(vec3_from(fit));
+    // one matrix rather than a translation either side of the scale, so the square case multiplies out to exactly identity
+    framing.m[3][0] = 0.5 - extent[0] / 2.0 * fit;
+    framing.m[3][1] = 0.5 - extent[1] / 2.0 * fit;
+    framing
+}
 
+// framing, rotation, scale, position, projection, in the order they get multiplied
+pub fn camera_matrices(camera_offset: FloatyCameraOffsets, extent: [f32; 2]) -> [Matrix4x4; 5] {
+    // drift is applied in angle space, then converted to a unit quaternion so the rotation can't scale or skew the scene
+    let rotation = Matrix4x4::rotation(quat_euler(CAMERA_ANGLE + camera_offset.rotation));
     let scale = Matrix4x4::scale(vec3_from(40.0));
-    Matrix4x4::mul_simd(&scale);
-
     let position = Matrix4x4::translation(Vector3::new(-18.0, -18.0, -40.0) + camera_offset.translation);
-    Matrix4x4::mul_simd(&position);
+    let projection = Matrix4x4::projection_perspective(ASPECT, 1.0, 0.1, 200.0);
 
-    let projection = Matrix4x4::projection_perspective(640.0 / 480.0, 1.0, 0.1, 200.0);
-    Matrix4x4::mul_simd(&projection);
+    [board_framing(extent), rotation, scale, position, projection]
+}
+
+/*
+focus is the point being followed in board units, heading is degrees counterclockwise from up
+the board is moved so focus is at the origin and turned by -heading, then tilted back like the fixed camera
+*/
+pub fn chase_camera_matrices(camera_offset: FloatyCameraOffsets, focus: Vector3, heading: f32) -> [Matrix4x4; 6] {
+    let center = Matrix4x4::translation(focus * -1.0);
+    let turn = Matrix4x4::rotation(quat_euler(vec3(0.0, 0.0, -heading)));
+    // the floaty drift is toned down, this close to the board the full amount is a lot
+    let tilt = Matrix4x4::rotation(quat_euler(CHASE_ANGLE + camera_offset.rotation * 0.5));
+    let scale = Matrix4x4::scale(vec3_from(40.0));
+    let position = Matrix4x4::translation(Vector3::new(0.0, -4.0, -22.0) + camera_offset.translation * 0.5);
+    let projection = Matrix4x4::projection_perspective(ASPECT, 1.0, 0.1, 200.0);
 
-    Matrix4x4::transform_vertex_simd(tris, offset_of!(vdp::Vertex => position));
+    [center, turn, tilt, scale, position, projection]
+}
 
-    vdp::draw_geometry(vdp::Topology::TriangleList, &tris);
+// the SIMD version of this lives in render/console.rs
+pub fn transform_draw_tris<R: Renderer>(renderer: &mut R, tris: &mut Vec<vdp::Vertex>, camera: &[Matrix4x4]) {
+    renderer.set_camera(camera);
+    renderer.submit(tris);
 }
 
 pub fn floor_box(other: &mut Vec<vdp::Vertex>, x: f32, y: f32, z: f32, size: f32, color: Vector4) {
@@ -49,58 +94,151 @@ pub fn floor_box(other: &mut Vec<vdp::Vertex>, x: f32, y: f32, z: f32, size: f32
     other.append(&mut square.tris());
 }
 
-pub fn food_box(other: &mut Vec<vdp::Vertex>, x: f32, y: f32, z: f32, size: f32, state_floaty: StateFloaty) {
-    let from = vec3(
-        x * size,
-        y * size,
-        z * size
-    );
-    let to = vec3(
-        (x + 1.0) * size,
-        (y + 1.0) * size,
-        (z + 1.0) * size
-    );
+// cell is the corner of the cell in cells, like body_segment
+pub fn food_box(other: &mut Vec<vdp::Vertex>, cell: Vector3, size: f32, color: Vector4, state_floaty: StateFloaty, tick: u32) {
+    let from = cell * size;
+    let to = (cell + vec3_from(1.0)) * size;
     let scale = 1.0 / 3.0;
-    let color = Vector4::new(1.0, 0.0, 0.0, 1.0);
 
     let c = Cube::new(from, to, vec3_from(scale), color, weight::CENTER);
-    let mut verts = state_floaty.float(c.tris(), size * scale);
+    let mut verts = state_floaty.float(c.tris(), size * scale, tick);
     other.append(&mut verts);
 }
 
-pub fn body_box(other: &mut Vec<vdp::Vertex>, head: bool, x: f32, y: f32, z: f32, size: f32, scale: f32) {
-    let from = vec3(
-        x * size,
-        y * size,
-        z * size
-    );
-    let to = vec3(
-        (x + 1.0) * size,
-        (y + 1.0) * size,
-        (z + 1.0) * size
-    );
-    let scale = vec3_from(if head { 0.95 } else { 0.85 } * scale);
-    let color = if head { Vector4::new(0.4, 1.0, 0.4, 1.0) } else { Vector4::new(0.0, 1.0, 0.0, 1.0) };
-
-    let c = BodyCube::new(from, to, scale, color, weight::Z1, 0.6, 0.4);
-    other.append(&mut c.tris());
-}
-
-pub fn body_prediction_box(other: &mut Vec<vdp::Vertex>, head: bool, x: f32, y: f32, z: f32, size: f32, weight: Vector3, state_floaty: StateFloaty) {
-    let from = vec3(
-        x * size,
-        y * size,
-        z * size
-    );
-    let to = vec3(
-        (x + 1.0) * size,
-        (y + 1.0) * size,
-        (z + 1.0) * size
-    );
+pub fn body_prediction_box(other: &mut Vec<vdp::Vertex>, color: Vector4, cell: Vector3, size: f32, weight: Vector3, state_floaty: StateFloaty, tick: u32) {
+    let from = cell * size;
+    let to = (cell + vec3_from(1.0)) * size;
     let scale = 0.5;
-    let color = if head { Vector4::new(0.4, 1.0, 0.4, 1.0) } else { Vector4::new(0.0, 1.0, 0.0, 1.0) };
 
     let c = Cube::new(from, to, vec3_from(scale), color, weight);
-    let mut verts = state_floaty.float(c.tris(), size * scale);
+    let mut verts = state_floaty.float(c.tris(), size * scale, tick);
     other.append(&mut verts);
 }
+
+// in cells, the body narrows over the last few cells before the tail
+static BODY_HALF_WIDTH: f32 = 0.38;
+static HEAD_HALF_WIDTH: f32 = 0.46;
+static TAPER_CELLS: f32 = 3.0;
+// height over half width
+static TUBE_HEIGHT: f32 = 1.8;
+
+// half width of the body this many cells from the tip of the tail, so neighbouring segments meet at the same width
+fn taper(from_tail: f32) -> f32 {
+    BODY_HALF_WIDTH * (0.35 + 0.65 * (from_tail / TAPER_CELLS).clamp(0.0, 1.0))
+}
+
+/*
+how a piece of the snake's tube runs through its cell
+    forward is the way to the next segment towards the head, for the head it's the way it's facing
+    back is the way to the next one towards the tail, None for the tail or a head on its own
+    from_tail is how many cells the middle of this one is from the tip of the tail
+*/
+#[derive(Clone, Copy)]
+pub struct Segment {
+    pub forward: Direction,
+    pub back: Option<Direction>,
+    pub from_tail: f32,
+}
+
+/*
+one cell of the snake's body as part of a single tube, cell is the corner of the cell in cells
+straight through, the tube runs edge to edge, on a turn it curves round the inside corner
+*/
+pub fn body_segment(other: &mut Vec<vdp::Vertex>, cell: Vector3, size: f32, segment: Segment, color: Vector4) {
+    let center = cell + vec3(0.5, 0.5, 0.0);
+    let Segment { forward, back, from_tail } = segment;
+    let f = forward.vector();
+    let mut path = Vec::new();
+
+    match back {
+        None => {
+            path.push((center, taper(from_tail)));
+            path.push((center + f * 0.5, taper(from_tail + 0.5)));
+        }
+        Some(back) if back == forward.opposite() => {
+            let b = back.vector();
+            path.push((center + b * 0.5, taper(from_tail - 0.5)));
+            path.push((center, taper(from_tail)));
+            path.push((center + f * 0.5, taper(from_tail + 0.5)));
+        }
+        Some(back) => {
+            // a quarter circle round the corner between the two edges
+            let b = back.vector();
+            let corner = center + (b + f) * 0.5;
+            let steps = 4;
+            for i in 0..(steps + 1) {
+                let t = i as f32 / steps as f32;
+                let angle = t * std::f32::consts::FRAC_PI_2;
+                let point = corner - f * (0.5 * angle.cos()) - b * (0.5 * angle.sin());
+                path.push((point, taper(from_tail - 0.5 + t)));
+            }
+        }
+    }
+
+    tube(other, path, size, color);
+}
+
+/*
+the head, a rounded snout pointing the way the snake is facing with a pair of eyes on top
+    from_tail is counted the same as for body_segment so the neck meets the body, scale shrinks the head of a short snake
+*/
+pub fn head_segment(other: &mut Vec<vdp::Vertex>, cell: Vector3, size: f32, segment: Segment, scale: f32, theme: &Theme) {
+    let center = cell + vec3(0.5, 0.5, 0.0);
+    let Segment { forward, back, from_tail } = segment;
+    let f = forward.vector();
+    let side = vec3(-f.y, f.x, 0.0);
+    let half_width = HEAD_HALF_WIDTH * scale;
+
+    let mut path = Vec::new();
+    match back {
+        Some(back) => path.push((center + back.vector() * 0.5, taper(from_tail - 0.5).min(half_width))),
+        None => path.push((center - f * 0.3 * scale, half_width)),
+    }
+    path.push((center, half_width));
+    path.push((center + f * 0.22 * scale, half_width));
+    path.push((center + f * 0.4 * scale, half_width * 0.55));
+    tube(other, path, size, theme.head);
+
+    // on the flat part of the top, either side of the middle
+    let top = cell.z + half_width * TUBE_HEIGHT;
+    for &direction in [-1.0, 1.0].iter() {
+        let eye = center + f * (0.1 * scale) + side * (direction * 0.2 * scale);
+        let eye = vec3(eye.x, eye.y, top);
+        let pupil = eye + f * (0.05 * scale) + vec3(0.0, 0.0, 0.03 * scale);
+        cell_cube(other, eye, 0.07 * scale, size, theme.eye);
+        cell_cube(other, pupil, 0.04 * scale, size, theme.pupil);
+    }
+}
+
+/*
+moves a segment's verts by its float offset, blending towards its neighbours' offsets nearer the edges they share
+    both sides of a shared edge end up at the average of the two, so the tube stays joined however they drift
+    offsets are in board units, neighbours are the direction they're in and their offset
+*/
+pub fn float_joined(verts: &mut [vdp::Vertex], x: f32, y: f32, size: f32, own: Vector3, forward: Option<(Direction, Vector3)>, back: Option<(Direction, Vector3)>) {
+    let center = vec3(x + 0.5, y + 0.5, 0.0);
+    for vert in verts.iter_mut() {
+        let local = vec3(vert.position.x / size - center.x, vert.position.y / size - center.y, 0.0);
+        let mut offset = own;
+        for (direction, other) in [forward, back].iter().flatten() {
+            // 0 at the middle of the cell up to a half at the edge
+            let toward = Vector3::dot(&local, &direction.vector()).clamp(0.0, 0.5);
+            offset = offset + (*other - own) * toward;
+        }
+        vert.position.x += offset.x;
+        vert.position.y += offset.y;
+        vert.position.z += offset.z;
+    }
+}
+
+// path in cells, scaled to the board
+fn tube(other: &mut Vec<vdp::Vertex>, path: Vec<(Vector3, f32)>, size: f32, color: Vector4) {
+    let path = path.into_iter().map(|(point, half_width)| (point * size, half_width * size)).collect();
+    other.append(&mut Tube::new(path, TUBE_HEIGHT, color).tris());
+}
+
+// a cube around a point in cells
+fn cell_cube(other: &mut Vec<vdp::Vertex>, center: Vector3, half: f32, size: f32, color: Vector4) {
+    let c = Cube::new((center - vec3_from(half)) * size, (center + vec3_from(half)) * size, vec3_from(1.0), color, weight::CENTER);
+    other.append(&mut c.tris());
+}
@@ -0,0 +1,190 @@
+use dbsdk_rs::{math::{Vector3, Vector4}, vdp};
+
+use crate::animation::track::Lerp;
+use crate::geometry::square::Square;
+use crate::geometry::weight::CENTER;
+use crate::theme;
+use crate::util::{vec3, vec3_from, vec4};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FloorPattern {
+    Flat,
+    Checkerboard,
+    Gradient,
+}
+
+impl FloorPattern {
+    pub fn name(self) -> &'static str {
+        match self {
+            FloorPattern::Flat => "FLAT",
+            FloorPattern::Checkerboard => "CHECKERBOARD",
+            FloorPattern::Gradient => "GRADIENT",
+        }
+    }
+
+    // the other way round from `as u8`, anything unknown is the checkerboard the board always had
+    pub fn from_u8(value: u8) -> FloorPattern {
+        match value {
+            0 => FloorPattern::Flat,
+            2 => FloorPattern::Gradient,
+            _ => FloorPattern::Checkerboard,
+        }
+    }
+}
+
+pub static PATTERNS: [FloorPattern; 3] = [FloorPattern::Flat, FloorPattern::Checkerboard, FloorPattern::Gradient];
+
+/*
+pattern colors are multipliers on top of the floor effect colors
+    checker is used on every other cell
+    gradient goes from the bottom left to the top right of the board
+*/
+#[derive(Clone, Copy)]
+pub struct DressingPalette {
+    pub portal: [Vector4; 2],
+    pub grid: Vector4,
+    pub corner: Vector4,
+    pub checker: Vector4,
+    pub gradient: [Vector4; 2],
+}
+
+impl DressingPalette {
+    pub fn classic() -> DressingPalette {
+        theme::CLASSIC.dressing
+    }
+}
+
+// everything drawn around and between the cells
+#[derive(Clone, Copy)]
+pub struct BoardDressing {
+    pub border: bool,
+    pub grid_lines: bool,
+    pub corners: bool,
+    pub pattern: FloorPattern,
+    pub palette: DressingPalette,
+}
+
+impl BoardDressing {
+    pub fn classic() -> BoardDressing {
+        BoardDressing {
+            border: true,
+            grid_lines: true,
+            corners: true,
+            pattern: FloorPattern::Checkerboard,
+            palette: DressingPalette::classic(),
+        }
+    }
+
+    // multiplier for the floor tile at x, y
+    pub fn tint(&self, x: u16, y: u16, width: u16, height: u16) -> Vector4 {
+        match self.pattern {
+            FloorPattern::Flat => vec4(1.0, 1.0, 1.0, 1.0),
+            FloorPattern::Checkerboard => {
+                if (x as u32 + y as u32) % 2 == 1 { self.palette.checker } else { vec4(1.0, 1.0, 1.0, 1.0) }
+            }
+            FloorPattern::Gradient => {
+                let span = (width as f32 + height as f32 - 2.0).max(1.0);
+                let t = (x as f32 + y as f32) / span;
+                Vector4::lerp(self.palette.gradient[0], self.palette.gradient[1], t)
+            }
+        }
+    }
+
+    pub fn tris(&self, other: &mut Vec<vdp::Vertex>, width: u16, height: u16, size: f32, tick: u32) {
+        let w = width as f32;
+        let h = height as f32;
+
+        if self.grid_lines {
+            // sits just under the floor tiles so it only shows through the gaps between them
+            let t = 0.04;
+            let z = -0.01;
+            for x in 0..=width {
+                let x = x as f32;
+                strip(other, vec3(x - t, 0.0, z), vec3(x + t, h, z), size, self.palette.grid);
+            }
+            for y in 0..=height {
+                let y = y as f32;
+                strip(other, vec3(0.0, y - t, z), vec3(w, y + t, z), size, self.palette.grid);
+            }
+        }
+
+        if self.border {
+            // the board wraps, so the edges are portals, a dashed strip with a shimmer running along it
+            let t = 0.1;
+            for x in 0..width {
+                let color = self.portal_color(x as u32, tick);
+                let x = x as f32;
+                dash(other, vec3(x, -t, 0.0), vec3(x + 1.0, 0.0, 0.0), size, color);
+                dash(other, vec3(x, h, 0.0), vec3(x + 1.0, h + t, 0.0), size, color);
+            }
+            for y in 0..height {
+                let color = self.portal_color(y as u32, tick);
+                let y = y as f32;
+                dash(other, vec3(-t, y, 0.0), vec3(0.0, y + 1.0, 0.0), size, color);
+                dash(other, vec3(w, y, 0.0), vec3(w + t, y + 1.0, 0.0), size, color);
+            }
+        }
+
+        if self.corners {
+            // little L shapes just outside each corner
+            let t = 0.12;
+            let l = 0.6;
+            let o = 0.25;
+            for &(cx, cy, sx, sy) in [(0.0, 0.0, -1.0, -1.0), (w, 0.0, 1.0, -1.0), (0.0, h, -1.0, 1.0), (w, h, 1.0, 1.0)].iter() {
+                let px = cx + sx * o;
+                let py = cy + sy * o;
+                strip(other, vec3(px, py, 0.0), vec3(px - sx * l, py + sy * t, 0.0), size, self.palette.corner);
+                strip(other, vec3(px, py, 0.0), vec3(px + sx * t, py - sy * l, 0.0), size, self.palette.corner);
+            }
+        }
+    }
+
+    fn portal_color(&self, i: u32, tick: u32) -> Vector4 {
+        let phase = (tick.wrapping_add(i.wrapping_mul(8)) % 64) as f32 / 64.0;
+        let t = (phase * 2.0 * std::f32::consts::PI).sin() * 0.5 + 0.5;
+        Vector4::lerp(self.palette.portal[0], self.palette.portal[1], t)
+    }
+}
+
+// from and to are in cells
+fn strip(other: &mut Vec<vdp::Vertex>, from: Vector3, to: Vector3, size: f32, color: Vector4) {
+    let square = Square::new(from * size, to * size, vec3_from(1.0), color, CENTER);
+    other.append(&mut square.tris());
+}
+
+// shrunk along its length so neighbouring dashes have a gap between them
+fn dash(other: &mut Vec<vdp::Vertex>, from: Vector3, to: Vector3, size: f32, color: Vector4) {
+    let scale = if (to.x - from.x).abs() > (to.y - from.y).abs() { vec3(0.8, 1.0, 1.0) } else { vec3(1.0, 0.8, 1.0) };
+    let square = Square::new(from * size, to * size, scale, color, CENTER);
+    other.append(&mut square.tris());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_sessions_and_wide_boards() {
+        let dressing = BoardDressing::classic();
+        let mut tris = Vec::new();
+        dressing.tris(&mut tris, u16::MAX, 1, 0.001, u32::MAX);
+        // a strip for every line between and around the cells, both ways
+        assert!(tris.len() >= (u16::MAX as usize + 1 + 2) * 6);
+    }
+
+    #[test]
+    fn patterns() {
+        let mut dressing = BoardDressing::classic();
+        for &pattern in PATTERNS.iter() {
+            assert!(FloorPattern::from_u8(pattern as u8) == pattern);
+        }
+        dressing.pattern = FloorPattern::Flat;
+        assert!(dressing.tint(1, 0, 4, 4).x == 1.0);
+        dressing.pattern = FloorPattern::Checkerboard;
+        assert!(dressing.tint(1, 0, 4, 4).x == dressing.palette.checker.x);
+        assert!(dressing.tint(1, 1, 4, 4).x == 1.0);
+        dressing.pattern = FloorPattern::Gradient;
+        assert!(dressing.tint(0, 0, 4, 4).x == dressing.palette.gradient[0].x);
+        assert!(dressing.tint(3, 3, 4, 4).x == dressing.palette.gradient[1].x);
+    }
+}
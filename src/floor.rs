@@ -0,0 +1,178 @@
+use dbsdk_rs::math::Vector4;
+
+use crate::animation::{easing::Easing, track::{Lerp, Track}};
+use crate::theme;
+
+/*
+colors for the floor tile under each cell
+    food pulses between its two colors
+    trail starts at its color where the tail just left and fades back to empty
+    danger is blended in on the cell the head will move into next
+*/
+#[derive(Clone, Copy)]
+pub struct FloorPalette {
+    pub empty: Vector4,
+    pub occupied: Vector4,
+    pub food: [Vector4; 2],
+    pub trail: Vector4,
+    pub danger: Vector4,
+}
+
+impl FloorPalette {
+    pub fn classic() -> FloorPalette {
+        theme::CLASSIC.floor
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum FloorState {
+    Empty,
+    Occupied,
+    Food,
+}
+
+pub struct FloorEffects {
+    palette: FloorPalette,
+    pulse: Track<f32>,
+    pulse_ticks: u32,
+    trail_ticks: u16,
+    trail: Vec<u16>, // frames left on each cell's trail
+}
+
+impl FloorEffects {
+    pub fn new(palette: FloorPalette, cells: usize, pulse_ticks: u32, trail_ticks: u16) -> FloorEffects {
+        let half = pulse_ticks / 2;
+
+        FloorEffects {
+            palette,
+            pulse: Track::new(0.0)
+                .key(half, 1.0, Easing::SineInOut)
+                .key(pulse_ticks, 0.0, Easing::SineInOut),
+            pulse_ticks,
+            trail_ticks,
+            trail: vec![0; cells],
+        }
+    }
+
+    pub fn set_palette(&mut self, palette: FloorPalette) {
+        self.palette = palette;
+    }
+
+    pub fn reset(&mut self) {
+        for t in self.trail.iter_mut() {
+            *t = 0;
+        }
+    }
+
+    // for a board with a different number of cells, trails are cleared
+    pub fn resize(&mut self, cells: usize) {
+        self.trail = vec![0; cells];
+    }
+
+    pub fn tick(&mut self) {
+        for t in self.trail.iter_mut() {
+            if *t > 0 {
+                *t -= 1;
+            }
+        }
+    }
+
+    // the tail just moved out of this cell
+    pub fn leave(&mut self, index: usize) {
+        if index < self.trail.len() {
+            self.trail[index] = self.trail_ticks;
+        }
+    }
+
+    // danger is [0,1], how close the head is to moving into this cell
+    pub fn color(&self, index: usize, state: FloorState, danger: f32, tick: u32) -> Vector4 {
+        let mut color = match state {
+            FloorState::Empty => self.palette.empty,
+            FloorState::Occupied => self.palette.occupied,
+            FloorState::Food => {
+                let t = self.pulse.sample((tick % self.pulse_ticks) as f32);
+                Vector4::lerp(self.palette.food[0], self.palette.food[1], t)
+            }
+        };
+
+        if let FloorState::Empty = state {
+            let trail = self.trail.get(index).cloned().unwrap_or(0);
+            if trail > 0 {
+                let t = Easing::QuadIn.apply(trail as f32 / self.trail_ticks as f32);
+                color = Vector4::lerp(color, self.palette.trail, t);
+            }
+        }
+
+        if danger > 0.0 {
+            color = Vector4::lerp(color, self.palette.danger, danger);
+        }
+
+        color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::vec4;
+
+    fn palette() -> FloorPalette {
+        FloorPalette {
+            empty: vec4(0.0, 0.0, 0.0, 1.0),
+            occupied: vec4(0.5, 0.5, 0.5, 1.0),
+            food: [vec4(1.0, 0.0, 0.0, 1.0), vec4(0.0, 1.0, 0.0, 1.0)],
+            trail: vec4(0.0, 0.0, 1.0, 1.0),
+            danger: vec4(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+
+    fn close(a: Vector4, b: Vector4) -> bool {
+        (a.x - b.x).abs() < 0.0001 && (a.y - b.y).abs() < 0.0001 && (a.z - b.z).abs() < 0.0001 && (a.w - b.w).abs() < 0.0001
+    }
+
+    #[test]
+    fn food_pulses_and_repeats() {
+        let floor = FloorEffects::new(palette(), 4, 40, 10);
+        let food = |tick| floor.color(0, FloorState::Food, 0.0, tick);
+        assert!(close(food(0), palette().food[0]));
+        assert!(close(food(20), palette().food[1]));
+        assert!(close(food(40), palette().food[0]));
+        assert!(close(food(10), food(50)));
+        // on the way up between the two
+        assert!(food(10).y > 0.0 && food(10).y < 1.0);
+    }
+
+    #[test]
+    fn trail_fades_out() {
+        let mut floor = FloorEffects::new(palette(), 4, 40, 10);
+        floor.leave(2);
+        // nothing outside the board
+        floor.leave(100);
+        let trail = |floor: &FloorEffects| floor.color(2, FloorState::Empty, 0.0, 0).z;
+        assert!(trail(&floor) == 1.0);
+        assert!(close(floor.color(1, FloorState::Empty, 0.0, 0), palette().empty));
+        // only empty cells show it
+        assert!(close(floor.color(2, FloorState::Occupied, 0.0, 0), palette().occupied));
+
+        let mut last = trail(&floor);
+        for _ in 0..10 {
+            floor.tick();
+            assert!(trail(&floor) < last);
+            last = trail(&floor);
+        }
+        assert!(last == 0.0);
+        floor.tick();
+        assert!(trail(&floor) == 0.0);
+
+        floor.leave(2);
+        floor.reset();
+        assert!(trail(&floor) == 0.0);
+    }
+
+    #[test]
+    fn danger_blends_in() {
+        let floor = FloorEffects::new(palette(), 4, 40, 10);
+        assert!(close(floor.color(0, FloorState::Empty, 0.5, 0), vec4(0.5, 0.5, 0.5, 1.0)));
+        assert!(close(floor.color(0, FloorState::Occupied, 1.0, 0), palette().danger));
+    }
+}
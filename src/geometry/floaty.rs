@@ -4,9 +4,13 @@ use dbsdk_rs::{math::Vector3, vdp};
 
 use crate::rng::Rng;
 
+/*
+a sine drift on each axis, ticks is the period and tick_offset is where in it tick 0 lands
+    it doesn't keep time itself, everything is worked out from the tick it's asked about,
+    so drawing a frame twice or skipping a few gives the same motion and nothing drifts out of step
+*/
 #[derive(Clone, Copy)]
 pub struct StateFloaty {
-    pub tick: u32,
     pub ticks: Vector3,
     pub tick_offset: Vector3,
     pub magnitude: Vector3
@@ -15,30 +19,55 @@ pub struct StateFloaty {
 impl StateFloaty {
     pub fn new(ticks: Vector3, tick_offset: Vector3, magnitude: Vector3) -> StateFloaty {
         StateFloaty {
-            tick: 0,
             ticks,
             tick_offset,
             magnitude
         }
     }
 
-    fn translation(&self) -> Vector3 {
+    fn translation(&self, tick: u32) -> Vector3 {
         let mut t = Vector3::zero();
-        let tick = self.tick as f32;
 
-        t.x = (((tick + self.tick_offset.x) % self.ticks.x) / self.ticks.x * 2.0 * PI).sin() * self.magnitude.x / 2.0;
-        t.y = (((tick + self.tick_offset.y) % self.ticks.y) / self.ticks.y * 2.0 * PI).sin() * self.magnitude.y / 2.0;
-        t.z = (((tick + self.tick_offset.z) % self.ticks.z) / self.ticks.z * 2.0 * PI).sin() * self.magnitude.z / 2.0;
+        t.x = (phase(tick, self.tick_offset.x, self.ticks.x) * 2.0 * PI).sin() * self.magnitude.x / 2.0;
+        t.y = (phase(tick, self.tick_offset.y, self.ticks.y) * 2.0 * PI).sin() * self.magnitude.y / 2.0;
+        t.z = (phase(tick, self.tick_offset.z, self.ticks.z) * 2.0 * PI).sin() * self.magnitude.z / 2.0;
         
         t
     }
 
-    pub fn tick(&mut self) {
-        self.tick += 1;
+    /*
+    for things that don't keep their own state, the periods and offsets come from key
+        the same key always floats the same way, so something keyed by what it is rather than where it is keeps its phase as it moves
+    */
+    pub fn from_key(key: u32, magnitude: Vector3) -> StateFloaty {
+        let number = |i: u32, min: f32, max: f32| min + (hash(key.wrapping_mul(6).wrapping_add(i)) % 1000) as f32 / 1000.0 * (max - min);
+        StateFloaty {
+            ticks: Vector3::new(number(0, 90.0, 240.0), number(1, 90.0, 240.0), number(2, 90.0, 240.0)),
+            tick_offset: Vector3::new(number(3, 0.0, 120.0), number(4, 0.0, 120.0), number(5, 0.0, 120.0)),
+            magnitude,
+        }
+    }
+
+    // the same drift with every magnitude multiplied by amount
+    pub fn scaled(&self, amount: f32) -> StateFloaty {
+        StateFloaty {
+            magnitude: self.magnitude * amount,
+            ..*self
+        }
     }
 
-    pub fn float(&self, verts: Vec<vdp::Vertex>, size: f32) -> Vec<vdp::Vertex> {
-        let t = self.translation();
+    /*
+    how far things resting on the board move at tick
+        z bobs between 0 and the magnitude rather than either side of 0, so nothing sinks into the floor or under the grid lines
+    */
+    pub fn offset(&self, size: f32, tick: u32) -> Vector3 {
+        let mut t = self.translation(tick);
+        t.z += self.magnitude.z / 2.0;
+        t * size
+    }
+
+    pub fn float(&self, verts: Vec<vdp::Vertex>, size: f32, tick: u32) -> Vec<vdp::Vertex> {
+        let t = self.translation(tick);
         let mut new_verts = Vec::with_capacity(verts.len());
 
         for vert in verts {
@@ -56,6 +85,10 @@ impl StateFloaty {
 
 
 
+/*
+magnitudes are the full peak to peak swing of each axis
+    translation is in world units, rotation is in degrees
+*/
 #[derive(Clone, Copy)]
 pub struct FloatyCamera {
     pub ticks: [u32; 6],
@@ -65,7 +98,7 @@ pub struct FloatyCamera {
 
 pub struct FloatyCameraOffsets {
     pub translation: Vector3,
-    pub rotation: Vector3
+    pub rotation: Vector3 // euler angles in degrees
 }
 
 impl FloatyCamera {
@@ -106,7 +139,8 @@ impl FloatyCamera {
         ]
     }
 
-    pub fn offsets(&self, tick: f32) -> FloatyCameraOffsets {
+    // amount scales the whole drift, 1 is the magnitudes it was made with, tick is vsync ticks like StateFloaty
+    pub fn offsets(&self, tick: u32, amount: f32) -> FloatyCameraOffsets {
         let mut t = Vector3::zero();
         let mut r = Vector3::zero();
 
@@ -119,16 +153,91 @@ impl FloatyCamera {
         r.z = tick_progress_sin(tick, self.tick_offset[5], self.ticks[5]) * self.magnitude[5] / 2.0;
         
         FloatyCameraOffsets {
-            translation: t,
-            rotation: r
+            translation: t * amount,
+            rotation: r * amount
         }
     }
 }
 
-fn tick_progress(tick: f32, offset: f32, ticks: f32) -> f32 {
-    ((tick + offset) % ticks) as f32 / ticks as f32
+// 0 to 1 through the period, worked out in f64 so it's still smooth after the game's been running for days
+fn phase(tick: u32, offset: f32, ticks: f32) -> f32 {
+    ((tick as f64 + offset as f64) % ticks as f64 / ticks as f64) as f32
+}
+
+// spreads consecutive keys all over the range
+fn hash(key: u32) -> u32 {
+    let mut x = key.wrapping_mul(0x9e37_79b1);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x85eb_ca6b);
+    x ^= x >> 13;
+    x
+}
+
+fn tick_progress_sin(tick: u32, offset: u32, ticks: u32) -> f32 {
+    (phase(tick, offset as f32, ticks as f32) * 2.0 * PI).sin()
 }
 
-fn tick_progress_sin(tick: f32, offset: u32, ticks: u32) -> f32 {
-    (tick_progress(tick, offset as f32, ticks as f32) * 2.0 * PI).sin()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::vec3;
+
+    static MAGNITUDE: Vector3 = Vector3::new(0.06, 0.06, 0.2);
+
+    fn same(a: Vector3, b: Vector3) -> bool {
+        a.x == b.x && a.y == b.y && a.z == b.z
+    }
+
+    #[test]
+    fn drawing_twice_a_tick_doesnt_change_the_motion() {
+        let floaty = StateFloaty::from_key(7, MAGNITUDE);
+        let once: Vec<Vector3> = (0..600).map(|tick| floaty.offset(0.1, tick)).collect();
+        let twice: Vec<Vector3> = (0..600).map(|tick| {
+            let first = floaty.offset(0.1, tick);
+            let second = floaty.offset(0.1, tick);
+            assert!(same(first, second));
+            second
+        }).collect();
+        assert!(once.iter().zip(twice.iter()).all(|(a, b)| same(*a, *b)));
+
+        // and skipping ticks lands in the same place
+        assert!(same(floaty.offset(0.1, 599), once[599]));
+    }
+
+    #[test]
+    fn phase_comes_from_the_tick_and_key() {
+        let ticks = [0, 1, 59, 600, 123_456, u32::MAX];
+        for key in [0, 1, 2, 1000].iter() {
+            let floaty = StateFloaty::from_key(*key, MAGNITUDE);
+            for &tick in ticks.iter() {
+                let offset = floaty.offset(1.0, tick);
+                // made again later, or after evaluating other ticks, it's in the same place
+                assert!(same(StateFloaty::from_key(*key, MAGNITUDE).offset(1.0, tick), offset));
+                assert!(same(floaty.offset(1.0, tick), offset));
+                // floatiness only scales it
+                let half = floaty.scaled(0.5).offset(1.0, tick);
+                assert!((half.z - offset.z * 0.5).abs() < 0.0001);
+                // it never sinks below where it rests
+                assert!(offset.z >= 0.0 && offset.z <= MAGNITUDE.z);
+            }
+        }
+
+        // neighbouring keys drift differently
+        let a = StateFloaty::from_key(3, MAGNITUDE);
+        let b = StateFloaty::from_key(4, MAGNITUDE);
+        assert!(!same(a.ticks, b.ticks) && !same(a.tick_offset, b.tick_offset));
+        assert!(!same(a.offset(1.0, 100), b.offset(1.0, 100)));
+    }
+
+    #[test]
+    fn camera_follows_the_tick() {
+        let mut rng = Rng::from_seeds([1, 2]);
+        let camera = FloatyCamera::new(&mut rng, vec3(5.0, 5.0, 1.0), vec3(17.0, 6.0, 11.0), 80 * 60, 200 * 60, 0, 120 * 60);
+        for &tick in [0, 1, 1000, u32::MAX].iter() {
+            let offsets = camera.offsets(tick, 1.0);
+            assert!(same(offsets.translation, camera.offsets(tick, 1.0).translation));
+            assert!(offsets.translation.x.abs() <= 2.5 && offsets.rotation.x.abs() <= 8.5);
+            assert!(same(camera.offsets(tick, 0.0).rotation, Vector3::zero()));
+        }
+    }
 }
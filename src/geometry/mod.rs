@@ -3,3 +3,4 @@ pub mod square;
 pub mod weight;
 pub mod floaty;
 pub mod body_cube;
+pub mod tube;
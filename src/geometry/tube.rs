@@ -0,0 +1,81 @@
+use dbsdk_rs::{math::{Vector3, Vector4}, vdp};
+
+use crate::util::{vec3, vec3_to4};
+
+use super::cube::square_tris;
+
+// the cross section, lateral offset and height as fractions of the half width and height, from the left foot round to the right
+// same shape as a BodyCube, straight sides up to 0.6 then cut in towards the top
+static PROFILE: [[f32; 2]; 6] = [
+    [-1.0, 0.0],
+    [-1.0, 0.6],
+    [-0.6, 1.0],
+    [0.6, 1.0],
+    [1.0, 0.6],
+    [1.0, 0.0],
+];
+// the faces between profile points get darker the steeper they are, there's no lighting so this is what makes it read as round
+static SHADE: [f32; 5] = [0.7, 0.85, 1.0, 0.85, 0.7];
+
+/*
+a shape swept along a path on the floor
+    each point is where the middle of the tube is and its half width there, the height is the half width times height
+    both ends are capped, so tubes that meet end to end look like one
+*/
+pub struct Tube {
+    pub path: Vec<(Vector3, f32)>,
+    pub height: f32,
+    pub color: Vector4,
+}
+
+impl Tube {
+    pub fn new(path: Vec<(Vector3, f32)>, height: f32, color: Vector4) -> Tube {
+        Tube {
+            path,
+            height,
+            color,
+        }
+    }
+
+    // the profile at path point i, turned to face along the path
+    fn ring(&self, i: usize) -> [Vector4; 6] {
+        let (center, half_width) = self.path[i];
+        let before = self.path[i.saturating_sub(1)].0;
+        let after = self.path[(i + 1).min(self.path.len() - 1)].0;
+
+        // averaged either side so corners bend smoothly
+        let along = after - before;
+        let length = (along.x * along.x + along.y * along.y).sqrt().max(0.00001);
+        let side = vec3(-along.y / length, along.x / length, 0.0);
+
+        let mut ring = [Vector4::zero(); 6];
+        for (point, profile) in ring.iter_mut().zip(PROFILE.iter()) {
+            let offset = side * (profile[0] * half_width) + vec3(0.0, 0.0, profile[1] * half_width * self.height);
+            *point = vec3_to4(center + offset);
+        }
+        ring
+    }
+
+    pub fn tris(&self) -> Vec<vdp::Vertex> {
+        let mut tris = Vec::new();
+        if self.path.len() < 2 {
+            return tris
+        }
+
+        let rings: Vec<[Vector4; 6]> = (0..self.path.len()).map(|i| self.ring(i)).collect();
+        for pair in rings.windows(2) {
+            for face in 0..SHADE.len() {
+                let color = self.color * Vector4::new(SHADE[face], SHADE[face], SHADE[face], 1.0);
+                tris.append(&mut square_tris([pair[0][face], pair[0][face + 1], pair[1][face + 1], pair[1][face]], color));
+            }
+        }
+
+        for ring in [rings[0], rings[rings.len() - 1]].iter() {
+            let color = self.color * Vector4::new(0.8, 0.8, 0.8, 1.0);
+            tris.append(&mut square_tris([ring[0], ring[1], ring[4], ring[5]], color));
+            tris.append(&mut square_tris([ring[1], ring[2], ring[3], ring[4]], color));
+        }
+
+        tris
+    }
+}
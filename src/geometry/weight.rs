@@ -25,20 +25,20 @@ impl Container {
 }
 
 pub fn size(vertices: Vec<Vector3>) -> Container {
-    if vertices.len() == 0 {
+    if vertices.is_empty() {
         return Container::zero();
     }
 
-    let mut from = Vector3::from(vertices[0]);
-    let mut to = Vector3::from(vertices[0]);
+    let mut from = vertices[0];
+    let mut to = vertices[0];
 
-    for i in 1..vertices.len() {
-        from.x = min(from.x, vertices[i].x);
-        from.y = min(from.y, vertices[i].y);
-        from.z = min(from.z, vertices[i].z);
-        to.x = max(to.x, vertices[i].x);
-        to.y = max(to.y, vertices[i].y);
-        to.z = max(to.z, vertices[i].z);
+    for vertex in vertices.iter().skip(1) {
+        from.x = min(from.x, vertex.x);
+        from.y = min(from.y, vertex.y);
+        from.z = min(from.z, vertex.z);
+        to.x = max(to.x, vertex.x);
+        to.y = max(to.y, vertex.y);
+        to.z = max(to.z, vertex.z);
     }
 
     Container::new(from, to, to - from)
@@ -52,11 +52,11 @@ pub static Z1: Vector3 = Vector3::new(0.5, 0.5, 0.0);
 pub static Z2: Vector3 = Vector3::new(0.5, 0.5, 1.0);
 pub static CENTER: Vector3 = Vector3::new(0.5, 0.5, 0.5);
 
-pub fn scale(vertices: &mut Vec<Vector3>, scale: Vector3, weight: Vector3) {
-    let s = size(vertices.clone());
+pub fn scale(vertices: &mut [Vector3], scale: Vector3, weight: Vector3) {
+    let s = size(vertices.to_vec());
 
-    for i in 0..vertices.len() {
-        let mut norm = (vertices[i] - s.from) / s.diff;
+    for vertex in vertices.iter_mut() {
+        let mut norm = (*vertex - s.from) / s.diff;
         norm = weight - (weight - norm) * scale;
         if s.diff.x == 0.0 {
             norm.x = 0.0;
@@ -67,6 +67,6 @@ pub fn scale(vertices: &mut Vec<Vector3>, scale: Vector3, weight: Vector3) {
         if s.diff.z == 0.0 {
             norm.z = 0.0;
         }
-        vertices[i] = s.from + norm * s.diff;
+        *vertex = s.from + norm * s.diff;
     }
 }
@@ -0,0 +1,54 @@
+/*
+dbsdk-rs defines `malloc`, `free` and `__errno_location` itself so C code on the console has a heap and errno
+linked into a host binary those definitions win over libc's, which breaks pretty much everything
+    rust's allocator calls malloc, which calls back into rust's allocator until the stack runs out
+    libc hands its own allocations to dbsdk's free
+    io errors always read as errno 0
+
+.cargo/config.toml links host builds with
+    --exclude-libs=ALL so libc keeps using its own functions
+    --wrap for each of those symbols so rust's std calls the functions below instead
+
+this only exists so host builds (the terminal frontend, golden image renders) can actually run, nothing in here
+is used on the console
+*/
+use std::os::raw::{c_char, c_int, c_void};
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+extern "C" {
+    fn __libc_malloc(size: usize) -> *mut c_void;
+    fn __libc_free(ptr: *mut c_void);
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+}
+
+const RTLD_NEXT: *mut c_void = -1isize as *mut c_void;
+
+static ERRNO_LOCATION: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+
+/// # Safety
+/// same as libc's malloc, only ever called in its place
+#[no_mangle]
+pub unsafe extern "C" fn __wrap_malloc(size: usize) -> *mut c_void {
+    __libc_malloc(size)
+}
+
+/// # Safety
+/// same as libc's free, ptr has to have come from __wrap_malloc or libc
+#[no_mangle]
+pub unsafe extern "C" fn __wrap_free(ptr: *mut c_void) {
+    __libc_free(ptr)
+}
+
+// libc doesn't export a second name for this one, so it's looked up past our own binary
+/// # Safety
+/// same as libc's __errno_location
+#[no_mangle]
+pub unsafe extern "C" fn __wrap___errno_location() -> *mut c_int {
+    let mut f = ERRNO_LOCATION.load(Ordering::Relaxed);
+    if f.is_null() {
+        f = dlsym(RTLD_NEXT, b"__errno_location\0".as_ptr() as *const c_char);
+        ERRNO_LOCATION.store(f, Ordering::Relaxed);
+    }
+    let f: extern "C" fn() -> *mut c_int = std::mem::transmute(f);
+    f()
+}
@@ -0,0 +1,331 @@
+use dbsdk_rs::gamepad::{GamepadButton, GamepadState};
+
+use snake::{Direction, Turn};
+use settings::Settings;
+use stick::{StickConfig, StickReader};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    TurnUp,
+    TurnDown,
+    TurnLeft,
+    TurnRight,
+    RotateLeft,
+    RotateRight,
+    Pause,
+    Confirm,
+    Back,
+}
+
+pub static ACTIONS: [Action; 9] = [
+    Action::TurnUp, Action::TurnDown, Action::TurnLeft, Action::TurnRight,
+    Action::RotateLeft, Action::RotateRight,
+    Action::Pause, Action::Confirm, Action::Back,
+];
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::TurnUp => "TURN UP",
+            Action::TurnDown => "TURN DOWN",
+            Action::TurnLeft => "TURN LEFT",
+            Action::TurnRight => "TURN RIGHT",
+            Action::RotateLeft => "ROTATE LEFT",
+            Action::RotateRight => "ROTATE RIGHT",
+            Action::Pause => "PAUSE",
+            Action::Confirm => "CONFIRM",
+            Action::Back => "BACK",
+        }
+    }
+
+    pub fn direction(self) -> Option<Direction> {
+        match self {
+            Action::TurnUp => Some(Direction::Up),
+            Action::TurnDown => Some(Direction::Down),
+            Action::TurnLeft => Some(Direction::Left),
+            Action::TurnRight => Some(Direction::Right),
+            _ => None,
+        }
+    }
+}
+
+/*
+how turns are read, chosen per player
+    absolute, the four turn actions point the snake up, down, left or right on the board
+    relative, left and right turn the snake from the way it's heading and up and down do nothing
+the rotate actions always turn relative to the heading
+*/
+#[derive(Clone, Copy, PartialEq)]
+pub enum ControlScheme {
+    Absolute,
+    Relative,
+}
+
+impl ControlScheme {
+    pub fn name(self) -> &'static str {
+        match self {
+            ControlScheme::Absolute => "ABSOLUTE",
+            ControlScheme::Relative => "RELATIVE",
+        }
+    }
+}
+
+// what the player asked the snake to do this frame
+#[derive(Clone, Copy, PartialEq)]
+pub enum Steer {
+    Face(Direction),
+    Turn(Turn),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+// anything an action can be bound to
+#[derive(Clone, Copy, PartialEq)]
+pub enum Binding {
+    Button(GamepadButton),
+    Stick(Stick, Direction),
+}
+
+// in bit order, so a button's index here is also its bit in the button mask
+static BUTTONS: [GamepadButton; 16] = [
+    GamepadButton::A, GamepadButton::B, GamepadButton::X, GamepadButton::Y,
+    GamepadButton::Up, GamepadButton::Down, GamepadButton::Left, GamepadButton::Right,
+    GamepadButton::L1, GamepadButton::L2, GamepadButton::L3,
+    GamepadButton::R1, GamepadButton::R2, GamepadButton::R3,
+    GamepadButton::Select, GamepadButton::Start,
+];
+static BUTTON_NAMES: [&str; 16] = [
+    "A", "B", "X", "Y",
+    "DPAD UP", "DPAD DOWN", "DPAD LEFT", "DPAD RIGHT",
+    "L1", "L2", "L3", "R1", "R2", "R3",
+    "SELECT", "START",
+];
+static DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+impl Binding {
+    pub fn name(self) -> String {
+        match self {
+            Binding::Button(button) => {
+                let index = BUTTONS.iter().position(|b| *b == button).unwrap_or(0);
+                BUTTON_NAMES[index].to_owned()
+            }
+            Binding::Stick(stick, direction) => {
+                let stick = if stick == Stick::Left { "LS" } else { "RS" };
+                let direction = match direction {
+                    Direction::Up => "UP",
+                    Direction::Down => "DOWN",
+                    Direction::Left => "LEFT",
+                    Direction::Right => "RIGHT",
+                };
+                format!("{} {}", stick, direction)
+            }
+        }
+    }
+
+    /*
+    one byte, for saving
+        0 is nothing bound
+        1 to 16 is a button, 1 + its bit
+        32 + stick * 4 + direction is a stick direction
+    */
+    pub fn encode(binding: Option<Binding>) -> u8 {
+        match binding {
+            None => 0,
+            Some(Binding::Button(button)) => 1 + BUTTONS.iter().position(|b| *b == button).unwrap_or(0) as u8,
+            Some(Binding::Stick(stick, direction)) => {
+                let stick = if stick == Stick::Left { 0 } else { 1 };
+                32 + stick * 4 + DIRECTIONS.iter().position(|d| *d == direction).unwrap_or(0) as u8
+            }
+        }
+    }
+
+    pub fn decode(byte: u8) -> Option<Binding> {
+        match byte {
+            1..=16 => Some(Binding::Button(BUTTONS[(byte - 1) as usize])),
+            32..=39 => {
+                let stick = if byte - 32 < 4 { Stick::Left } else { Stick::Right };
+                Some(Binding::Stick(stick, DIRECTIONS[((byte - 32) % 4) as usize]))
+            }
+            _ => None,
+        }
+    }
+}
+
+/*
+every action has a few slots it can be bound in
+    earlier slots win when more than one turn is held, the defaults put the d-pad first, then the left stick, then the right
+*/
+pub const BINDING_SLOTS: usize = 3;
+
+#[derive(Clone, Copy)]
+pub struct Bindings {
+    pub slots: [[Option<Binding>; BINDING_SLOTS]; 9],
+}
+
+impl Bindings {
+    pub const fn classic() -> Bindings {
+        Bindings {
+            slots: [
+                [Some(Binding::Button(GamepadButton::Up)), Some(Binding::Stick(Stick::Left, Direction::Up)), Some(Binding::Stick(Stick::Right, Direction::Up))],
+                [Some(Binding::Button(GamepadButton::Down)), Some(Binding::Stick(Stick::Left, Direction::Down)), Some(Binding::Stick(Stick::Right, Direction::Down))],
+                [Some(Binding::Button(GamepadButton::Left)), Some(Binding::Stick(Stick::Left, Direction::Left)), Some(Binding::Stick(Stick::Right, Direction::Left))],
+                [Some(Binding::Button(GamepadButton::Right)), Some(Binding::Stick(Stick::Left, Direction::Right)), Some(Binding::Stick(Stick::Right, Direction::Right))],
+                [Some(Binding::Button(GamepadButton::L1)), None, None],
+                [Some(Binding::Button(GamepadButton::R1)), None, None],
+                [Some(Binding::Button(GamepadButton::Start)), None, None],
+                [Some(Binding::Button(GamepadButton::A)), None, None],
+                [Some(Binding::Button(GamepadButton::B)), None, None],
+            ],
+        }
+    }
+
+    pub fn get(&self, action: Action) -> [Option<Binding>; BINDING_SLOTS] {
+        self.slots[action as usize]
+    }
+
+    pub fn set(&mut self, action: Action, slot: usize, binding: Binding) {
+        // the same input twice on one action just wastes a slot
+        for other in self.slots[action as usize].iter_mut() {
+            if *other == Some(binding) {
+                *other = None;
+            }
+        }
+        self.slots[action as usize][slot] = Some(binding);
+    }
+}
+
+/*
+reads a gamepad through the bindings
+    call update once a frame with the new state, pressed is only true on the frame something goes down
+*/
+pub struct Input {
+    pub bindings: Bindings,
+    pub scheme: ControlScheme,
+    pub sticks: [StickReader; 2],
+    state: GamepadState,
+    last: GamepadState,
+}
+
+impl Input {
+    pub fn new(settings: &Settings, player: usize) -> Input {
+        let config = StickConfig::classic(settings.stick_shape, settings.deadzone);
+        let mut input = Input {
+            bindings: settings.bindings,
+            scheme: ControlScheme::Absolute,
+            sticks: [StickReader::new(config), StickReader::new(config)],
+            state: empty_state(),
+            last: empty_state(),
+        };
+        input.apply(settings, player);
+        input
+    }
+
+    // picks up anything changed in the options menu, player is whose control scheme to use
+    pub fn apply(&mut self, settings: &Settings, player: usize) {
+        self.bindings = settings.bindings;
+        self.scheme = settings.control_schemes[player];
+        for stick in self.sticks.iter_mut() {
+            stick.config.shape = settings.stick_shape;
+            stick.config.inner = settings.deadzone;
+        }
+    }
+
+    pub fn update(&mut self, state: GamepadState) {
+        self.last = self.state;
+        self.state = state;
+        self.sticks[0].update(state.left_stick_x, state.left_stick_y);
+        self.sticks[1].update(state.right_stick_x, state.right_stick_y);
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.bindings.get(action).iter().any(|b| b.is_some_and(|b| self.active(b, true) && !self.active(b, false)))
+    }
+
+    // which way the player is steering, slot order first and then up, down, left, right
+    pub fn turn(&self) -> Option<Direction> {
+        for slot in 0..BINDING_SLOTS {
+            for action in ACTIONS[..4].iter() {
+                if let Some(binding) = self.bindings.get(*action)[slot] {
+                    if self.active(binding, true) {
+                        return action.direction()
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /*
+    turns go through the control scheme
+        absolute turns are read while held, like the d-pad always was
+        relative turns only happen on the frame the button goes down, holding one shouldn't spin the snake
+    */
+    pub fn steer(&self) -> Option<Steer> {
+        if self.pressed(Action::RotateLeft) {
+            return Some(Steer::Turn(Turn::Left))
+        }
+        if self.pressed(Action::RotateRight) {
+            return Some(Steer::Turn(Turn::Right))
+        }
+        match self.scheme {
+            ControlScheme::Absolute => self.turn().map(Steer::Face),
+            ControlScheme::Relative => {
+                if self.pressed(Action::TurnLeft) {
+                    Some(Steer::Turn(Turn::Left))
+                } else if self.pressed(Action::TurnRight) {
+                    Some(Steer::Turn(Turn::Right))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    // the first input that went down this frame, for rebinding
+    pub fn captured(&self) -> Option<Binding> {
+        for button in BUTTONS.iter() {
+            let binding = Binding::Button(*button);
+            if self.active(binding, true) && !self.active(binding, false) {
+                return Some(binding)
+            }
+        }
+        for stick in [Stick::Left, Stick::Right].iter() {
+            for direction in DIRECTIONS.iter() {
+                let binding = Binding::Stick(*stick, *direction);
+                if self.active(binding, true) && !self.active(binding, false) {
+                    return Some(binding)
+                }
+            }
+        }
+        None
+    }
+
+    // whether a binding is down this frame, or last frame if now is false
+    fn active(&self, binding: Binding, now: bool) -> bool {
+        match binding {
+            Binding::Button(button) => {
+                let state = if now { self.state } else { self.last };
+                state.button_mask.contains(button)
+            }
+            Binding::Stick(stick, direction) => {
+                let stick = &self.sticks[stick as usize];
+                let current = if now { stick.direction } else { stick.last_direction };
+                current == Some(direction)
+            }
+        }
+    }
+}
+
+pub fn empty_state() -> GamepadState {
+    GamepadState {
+        button_mask: dbsdk_rs::gamepad::GamepadButtonMask::none(),
+        left_stick_x: 0,
+        left_stick_y: 0,
+        right_stick_x: 0,
+        right_stick_y: 0,
+    }
+}
@@ -1,66 +1,285 @@
 extern crate dbsdk_rs;
+#[cfg(target_arch = "wasm32")]
+use std::ptr;
+#[cfg(target_arch = "wasm32")]
 use dbsdk_rs::{vdp, db, gamepad};
+#[cfg(target_arch = "wasm32")]
+use render::{Renderer, console::VdpRenderer};
+#[cfg(target_arch = "wasm32")]
+use sound::{sfx::{Sfx, SoundEffects}, music::Music, song::Song};
+#[cfg(target_arch = "wasm32")]
+use input::{Action, Input, Steer};
+#[cfg(target_arch = "wasm32")]
+use menu::{Menu, MenuEvent};
+#[cfg(target_arch = "wasm32")]
+use rumble::{NoMotor, Rumble};
+#[cfg(target_arch = "wasm32")]
+use controllers::Controllers;
+#[cfg(target_arch = "wasm32")]
+use settings::PLAYERS;
+#[cfg(target_arch = "wasm32")]
+use save::Saves;
+#[cfg(target_arch = "wasm32")]
+use card::MemoryCard;
 
-mod draw;
-mod snake;
-mod rng;
-mod geometry;
-mod util;
+pub mod draw;
+pub mod snake;
+pub mod rng;
+pub mod geometry;
+pub mod util;
+pub mod animation;
+pub mod floor;
+pub mod dressing;
+pub mod particles;
+pub mod raster;
+pub mod render;
+pub mod font;
+pub mod sound;
+pub mod settings;
+pub mod input;
+pub mod menu;
+pub mod card;
+pub mod stick;
+pub mod rumble;
+pub mod controllers;
+pub mod save;
+pub mod theme;
+pub mod skin;
+pub mod backdrop;
 
-static mut GAME: Option<snake::Game> = None;
-static mut CONTROLLER: Option<gamepad::Gamepad> = None;
+#[cfg(all(not(target_arch = "wasm32"), target_os = "linux"))]
+pub mod host;
 
+// everything below only exists on the console, host builds just use the modules above
+
+#[cfg(target_arch = "wasm32")]
+static PADS: [gamepad::Gamepad; PLAYERS] = [
+    gamepad::Gamepad::new(gamepad::GamepadSlot::SlotA),
+    gamepad::Gamepad::new(gamepad::GamepadSlot::SlotB),
+    gamepad::Gamepad::new(gamepad::GamepadSlot::SlotC),
+    gamepad::Gamepad::new(gamepad::GamepadSlot::SlotD),
+];
+
+// everything the tick function needs, kept together so there's only one static to get at
+#[cfg(target_arch = "wasm32")]
+struct Console {
+    game: snake::Game,
+    controllers: Controllers,
+    renderer: VdpRenderer,
+    settings: settings::Settings,
+    input: Input,
+    menu: Menu,
+    rumble: Rumble,
+    rumble_slot: usize, // the pad rumble was last written to
+    saves: Saves,
+    sounds: Option<SoundEffects>, // the game still works without sound if the samples don't fit
+    music: Option<Music>,
+}
+
+#[cfg(target_arch = "wasm32")]
+static mut CONSOLE: Option<Console> = None;
+
+// only main and the vsync handler touch it, and main is done before the first tick
+#[cfg(target_arch = "wasm32")]
+fn console() -> &'static mut Console {
+    unsafe { (*ptr::addr_of_mut!(CONSOLE)).as_mut().unwrap() }
+}
+
+#[cfg(target_arch = "wasm32")]
 fn tick() {
-    let game = unsafe { GAME.as_mut().unwrap() };
-    let controller = unsafe { CONTROLLER.as_mut().unwrap() };
-
-    // if this is uncommented the game will only work if an actual controller is connected, a keyboard doesn't count
-    // if controller.is_connected() {
-        let state = controller.read_state();
-        let deadzone = 0.2;
-
-        let gamepad = util::read_gamepad(state);
-        let left_stick = util::read_deadzone(deadzone, state.left_stick_x, state.left_stick_y);
-        let right_stick = util::read_deadzone(deadzone, state.right_stick_x, state.right_stick_y);
-        
-        if let Some(dir) = gamepad {
-            game.set_direction(dir);
-        } else if let Some(dir) = left_stick {
-            game.set_direction(dir);
-        } else if let Some(dir) = right_stick {
-            game.set_direction(dir);
+    let Console { game, controllers, renderer, settings, input, menu, rumble, rumble_slot, saves, sounds, music } = console();
+    let mut sounds = sounds.as_mut();
+
+    // every slot is read, a keyboard never shows up as connected so it's only noticed by its input
+    let connected = [PADS[0].is_connected(), PADS[1].is_connected(), PADS[2].is_connected(), PADS[3].is_connected()];
+    let states = [PADS[0].read_state(), PADS[1].read_state(), PADS[2].read_state(), PADS[3].read_state()];
+    let was_waiting = controllers.waiting;
+    controllers.update(connected, &states);
+    let slot = controllers.player_slot();
+
+    // a keyboard can't rumble
+    let pad = &PADS[slot];
+    let has_motor = connected[slot];
+
+    // a different player took over, whatever the old pad was doing is switched off before the new one is used
+    if slot != *rumble_slot {
+        rumble.stop(&PADS[*rumble_slot]);
+        *rumble_slot = slot;
+    }
+
+    menu.player = slot;
+    input.apply(settings, menu.player);
+    rumble.intensity = settings.rumble;
+    rumble.warnings = settings.rumble_warnings;
+    game.camera = settings.camera;
+    game.set_theme(settings.theme());
+    game.skin = *settings.skin();
+    game.floatiness = settings.floatiness;
+    game.set_floor_pattern(settings.floor_pattern);
+    input.update(states[slot]);
+
+    // coming back from a disconnect lands on the pause menu so the player can get ready
+    if was_waiting && !controllers.waiting {
+        menu.show();
+    }
+
+    // nothing to lose by writing to a pad that's gone, and it makes sure one that's still there stops
+    if controllers.waiting {
+        rumble.stop(pad);
+    } else if menu.open {
+        for event in menu.update(input, settings) {
+            match event {
+                MenuEvent::Moved => play(&mut sounds, Sfx::MenuMove),
+                MenuEvent::Selected => play(&mut sounds, Sfx::MenuSelect),
+                MenuEvent::Resume => {}
+                // a new game means the suspended one shouldn't come back next time
+                MenuEvent::Restart => {
+                    game.reset();
+                    let _ = save::discard(&mut MemoryCard);
+                }
+                MenuEvent::QuickSave => saves.quick_save(game),
+                // nothing happens if there's no quick save yet
+                MenuEvent::QuickLoad => { saves.quick_load(game); }
+                // no card is fine, the settings just won't stick
+                MenuEvent::SettingsChanged => {
+                    let _ = settings.save();
+                    // a different arena is a new game, the old one can't be carried over
+                    if [game.width, game.height] != settings.arena_size() {
+                        *game = new_game(settings);
+                        let _ = save::discard(&mut MemoryCard);
+                    }
+                }
+            }
         }
-    // }
+    } else if input.pressed(Action::Pause) {
+        menu.show();
+        rumble.stop(pad);
+        play(&mut sounds, Sfx::MenuSelect);
+        // pausing is the only time we know the player might walk away, no card just means no resume
+        let _ = save::suspend(&mut MemoryCard, game);
+    } else {
+        match input.steer() {
+            Some(Steer::Face(dir)) => game.set_direction(dir),
+            Some(Steer::Turn(turn)) => game.turn(turn),
+            None => {}
+        }
+
+        let result = game.tick();
 
-    match game.tick() {
-        snake::TickResult::Win(_msg) => {
-            // no win screen yet
-            // no reset so the game will intentionally hang here
-            game.draw();
+        // drained before a reset so the death sound still plays
+        let events = game.drain_events();
+        if let Some(sounds) = sounds.as_mut() {
+            sounds.play_events(&events);
         }
-        snake::TickResult::Lose(_msg) => {
-            // no lose screen yet
-            game.reset();
+        rumble.events(&events);
+        rumble.danger(game.danger_ahead());
+
+        // a new best might unlock skins, it's only written when it changes so a hanging win doesn't keep saving
+        if let snake::TickResult::Win(_) | snake::TickResult::Lose(_) = result {
+            if settings.record_score(game.score()) {
+                let _ = settings.save();
+            }
         }
-        snake::TickResult::Continue => {
-            game.draw();
+
+        match result {
+            snake::TickResult::Win(_msg) => {
+                // no win screen yet
+                // no reset so the game will intentionally hang here
+            }
+            snake::TickResult::Lose(_msg) => {
+                // no lose screen yet
+                game.reset();
+                let _ = save::discard(&mut MemoryCard);
+            }
+            snake::TickResult::Continue => {}
         }
     }
+
+    if has_motor {
+        rumble.tick(pad);
+    } else {
+        rumble.tick(&NoMotor);
+    }
+
+    // volumes are applied every frame so changes in the options menu are heard straight away
+    if let Some(sounds) = sounds.as_mut() {
+        sounds.volume = settings.sfx();
+    }
+    if let Some(music) = music.as_mut() {
+        music.volume = settings.music();
+        music.set_tempo(game.interval_frames());
+        music.update();
+    }
+
+    game.draw(renderer);
+    menu.draw(renderer, settings);
+    if controllers.waiting {
+        menu::draw_prompt(renderer, &settings.theme().ui, "CONTROLLER DISCONNECTED", &["RECONNECT A CONTROLLER", "OR PRESS ANY BUTTON"]);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn new_game(settings: &settings::Settings) -> snake::Game {
+    let size = settings.arena_size();
+    snake::Game::new(size[0], size[1], 3, 4, 8)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn play(sounds: &mut Option<&mut SoundEffects>, sfx: Sfx) {
+    if let Some(sounds) = sounds {
+        sounds.play(sfx);
+    }
 }
 
+#[cfg(target_arch = "wasm32")]
 #[no_mangle]
 pub fn main(_: i32, _: i32) -> i32 {
     db::register_panic();
-    vdp::depth_write(true);
-    vdp::depth_func(vdp::Compare::LessOrEqual);
-    
-    // unsafe so we can initialize the game and controller here and use them in the tick function
+
+    let mut renderer = VdpRenderer::new();
+    renderer.set_depth(true, vdp::Compare::LessOrEqual);
+
+    let settings = settings::Settings::load();
+    let mut game = new_game(&settings);
+    let mut menu = Menu::new();
+
+    // pick up where the last session paused, on the pause menu so it doesn't start moving straight away
+    if save::resume(&mut MemoryCard, &mut game) {
+        menu.show();
+    }
+
+    let mut sounds = SoundEffects::new(0, 8);
+    if let Some(sounds) = sounds.as_mut() {
+        sounds.volume = settings.sfx();
+    }
+
+    // sound effects get voices 0 to 7, music starts after them
+    // a broken song just means no music, the theme parsing is covered by song.rs tests
+    let mut music = Song::parse(sound::THEME).ok().and_then(|song| Music::new(song, 8));
+    if let Some(music) = music.as_mut() {
+        music.volume = settings.music();
+        music.play();
+    }
+
+    let console = Console {
+        game,
+        controllers: Controllers::new(),
+        renderer,
+        input: Input::new(&settings, 0),
+        menu,
+        rumble: Rumble::new(settings.rumble, settings.rumble_warnings),
+        rumble_slot: 0,
+        saves: Saves::new(),
+        settings,
+        sounds,
+        music,
+    };
+    // set before the vsync handler is, so tick always finds it
     unsafe {
-        GAME = Some(snake::Game::new(12, 12, 3, 4, 8));
-        CONTROLLER = Some(gamepad::Gamepad::new(gamepad::GamepadSlot::SlotA));
+        *ptr::addr_of_mut!(CONSOLE) = Some(console);
     }
 
     vdp::set_vsync_handler(Some(tick));
 
-    return 0;
+    0
 }
@@ -0,0 +1,349 @@
+use dbsdk_rs::math::Vector4;
+
+use font;
+use draw::CameraMode;
+use input::{Action, ControlScheme, Input, ACTIONS, BINDING_SLOTS};
+use render::{self, Renderer, SCREEN_WIDTH};
+use settings::{Settings, ARENAS};
+use stick::DeadzoneShape;
+use theme::{UiPalette, THEMES};
+use skin::SKINS;
+use dressing::PATTERNS;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Screen {
+    Pause,
+    Options,
+    Controls,
+}
+
+// what the menu wants the rest of the game to do
+#[derive(Clone, Copy, PartialEq)]
+pub enum MenuEvent {
+    Moved,
+    Selected,
+    Resume,
+    Restart,
+    QuickSave,
+    QuickLoad,
+    // the player left options or controls, settings should be applied and saved
+    SettingsChanged,
+}
+
+static PAUSE_ITEMS: [&str; 6] = ["RESUME", "OPTIONS", "CONTROLS", "QUICK SAVE", "QUICK LOAD", "RESTART"];
+static OPTION_ITEMS: [&str; 15] = [
+    "MASTER VOLUME", "SFX VOLUME", "MUSIC VOLUME", "STICK DEADZONE", "DEADZONE SHAPE",
+    "CONTROL SCHEME", "CAMERA", "RUMBLE", "DANGER RUMBLE", "ARENA SIZE", "THEME", "SKIN", "FLOATINESS",
+    "FLOOR PATTERN", "BACK",
+];
+// rows on the controls screen after the actions
+static CONTROL_ITEMS: [&str; 2] = ["RESET DEFAULTS", "BACK"];
+
+// how long rebinding waits for an input before giving up, in vsync ticks
+static CAPTURE_TICKS: u32 = 5 * 60;
+
+/*
+pause menu with options and controls screens under it
+    navigated with the turn actions, confirm and back, so it follows whatever the player has bound
+    rebinding a control waits for the next button or stick direction pushed, confirm included
+*/
+pub struct Menu {
+    pub open: bool,
+    // whose control scheme the options screen changes
+    pub player: usize,
+    pub screen: Screen,
+    cursor: usize,
+    column: usize,
+    capture: Option<u32>,
+}
+
+impl Default for Menu {
+    fn default() -> Menu {
+        Menu::new()
+    }
+}
+
+impl Menu {
+    pub fn new() -> Menu {
+        Menu {
+            open: false,
+            player: 0,
+            screen: Screen::Pause,
+            cursor: 0,
+            column: 0,
+            capture: None,
+        }
+    }
+
+    pub fn show(&mut self) {
+        self.open = true;
+        self.go_to(Screen::Pause, 0);
+    }
+
+    fn go_to(&mut self, screen: Screen, cursor: usize) {
+        self.screen = screen;
+        self.cursor = cursor;
+        self.column = 0;
+        self.capture = None;
+    }
+
+    fn rows(&self) -> usize {
+        match self.screen {
+            Screen::Pause => PAUSE_ITEMS.len(),
+            Screen::Options => OPTION_ITEMS.len(),
+            Screen::Controls => ACTIONS.len() + CONTROL_ITEMS.len(),
+        }
+    }
+
+    pub fn update(&mut self, input: &Input, settings: &mut Settings) -> Vec<MenuEvent> {
+        let mut events = Vec::new();
+        if !self.open {
+            return events
+        }
+
+        if let Some(ticks) = self.capture {
+            if let Some(binding) = input.captured() {
+                settings.bindings.set(ACTIONS[self.cursor], self.column, binding);
+                self.capture = None;
+                events.push(MenuEvent::Selected);
+            } else if ticks == 0 {
+                self.capture = None;
+            } else {
+                self.capture = Some(ticks - 1);
+            }
+            return events
+        }
+
+        if input.pressed(Action::TurnUp) {
+            self.cursor = (self.cursor + self.rows() - 1) % self.rows();
+            events.push(MenuEvent::Moved);
+        }
+        if input.pressed(Action::TurnDown) {
+            self.cursor = (self.cursor + 1) % self.rows();
+            events.push(MenuEvent::Moved);
+        }
+        let step = if input.pressed(Action::TurnLeft) { -1 } else if input.pressed(Action::TurnRight) { 1 } else { 0 };
+        if step != 0 && self.adjust(step, settings) {
+            events.push(MenuEvent::Moved);
+        }
+
+        if input.pressed(Action::Back) || input.pressed(Action::Pause) {
+            match self.screen {
+                Screen::Pause => {
+                    self.open = false;
+                    events.push(MenuEvent::Resume);
+                }
+                Screen::Options | Screen::Controls => {
+                    let cursor = if self.screen == Screen::Options { 1 } else { 2 };
+                    self.go_to(Screen::Pause, cursor);
+                    events.push(MenuEvent::SettingsChanged);
+                }
+            }
+            return events
+        }
+
+        if input.pressed(Action::Confirm) {
+            events.push(MenuEvent::Selected);
+            match (self.screen, self.cursor) {
+                (Screen::Pause, 0) => {
+                    self.open = false;
+                    events.push(MenuEvent::Resume);
+                }
+                (Screen::Pause, 1) => self.go_to(Screen::Options, 0),
+                (Screen::Pause, 2) => self.go_to(Screen::Controls, 0),
+                // both carry straight on playing
+                (Screen::Pause, 3) => {
+                    self.open = false;
+                    events.push(MenuEvent::QuickSave);
+                }
+                (Screen::Pause, 4) => {
+                    self.open = false;
+                    events.push(MenuEvent::QuickLoad);
+                }
+                (Screen::Pause, _) => {
+                    self.open = false;
+                    events.push(MenuEvent::Restart);
+                }
+                (Screen::Options, 14) => {
+                    self.go_to(Screen::Pause, 1);
+                    events.push(MenuEvent::SettingsChanged);
+                }
+                (Screen::Options, _) => {}
+                (Screen::Controls, row) if row < ACTIONS.len() => self.capture = Some(CAPTURE_TICKS),
+                (Screen::Controls, row) if row == ACTIONS.len() => settings.bindings = Settings::new().bindings,
+                (Screen::Controls, _) => {
+                    self.go_to(Screen::Pause, 2);
+                    events.push(MenuEvent::SettingsChanged);
+                }
+            }
+        }
+
+        events
+    }
+
+    // left and right change values on the options screen and pick a binding slot on the controls screen
+    fn adjust(&mut self, step: i32, settings: &mut Settings) -> bool {
+        match self.screen {
+            Screen::Pause => false,
+            Screen::Options => {
+                let (value, amount, min, max) = match self.cursor {
+                    0 => (&mut settings.master_volume, 0.1, 0.0, 1.0),
+                    1 => (&mut settings.sfx_volume, 0.1, 0.0, 1.0),
+                    2 => (&mut settings.music_volume, 0.1, 0.0, 1.0),
+                    3 => (&mut settings.deadzone, 0.05, 0.05, 0.9),
+                    // only two choices each, so either way flips them
+                    4 => {
+                        settings.stick_shape = if settings.stick_shape == DeadzoneShape::Radial { DeadzoneShape::Axial } else { DeadzoneShape::Radial };
+                        return true
+                    }
+                    5 => {
+                        let scheme = &mut settings.control_schemes[self.player];
+                        *scheme = if *scheme == ControlScheme::Absolute { ControlScheme::Relative } else { ControlScheme::Absolute };
+                        return true
+                    }
+                    6 => {
+                        settings.camera = if settings.camera == CameraMode::Fixed { CameraMode::Chase } else { CameraMode::Fixed };
+                        return true
+                    }
+                    7 => (&mut settings.rumble, 0.1, 0.0, 1.0),
+                    8 => {
+                        settings.rumble_warnings = !settings.rumble_warnings;
+                        return true
+                    }
+                    9 => {
+                        settings.arena = (settings.arena as i32 + step).rem_euclid(ARENAS.len() as i32) as usize;
+                        return true
+                    }
+                    10 => {
+                        settings.theme = (settings.theme as i32 + step).rem_euclid(THEMES.len() as i32) as usize;
+                        return true
+                    }
+                    // locked skins are skipped, the first is always unlocked so this can't go round forever
+                    11 => {
+                        let count = SKINS.len() as i32;
+                        let mut skin = settings.skin as i32;
+                        loop {
+                            skin = (skin + step).rem_euclid(count);
+                            if SKINS[skin as usize].unlocked(settings.best_score) {
+                                break
+                            }
+                        }
+                        settings.skin = skin as usize;
+                        return true
+                    }
+                    12 => (&mut settings.floatiness, 0.1, 0.0, 1.0),
+                    13 => {
+                        let index = PATTERNS.iter().position(|&pattern| pattern == settings.floor_pattern).unwrap_or(0) as i32;
+                        settings.floor_pattern = PATTERNS[(index + step).rem_euclid(PATTERNS.len() as i32) as usize];
+                        return true
+                    }
+                    _ => return false,
+                };
+                // rounded to the step so repeated presses don't drift
+                *value = ((*value + amount * step as f32) / amount).round() * amount;
+                *value = value.max(min).min(max);
+                true
+            }
+            Screen::Controls => {
+                if self.cursor >= ACTIONS.len() {
+                    return false
+                }
+                self.column = (self.column as i32 + step).max(0).min(BINDING_SLOTS as i32 - 1) as usize;
+                true
+            }
+        }
+    }
+
+    pub fn draw<R: Renderer>(&self, renderer: &mut R, settings: &Settings) {
+        if !self.open {
+            return
+        }
+        render::begin_overlay(renderer);
+        let ui = &settings.theme().ui;
+
+        // options rows are closer together so they all fit on screen
+        let (title, top, spacing) = match self.screen {
+            Screen::Pause => ("PAUSED", 150.0, 30.0),
+            Screen::Options => ("OPTIONS", 45.0, 24.0),
+            Screen::Controls => ("CONTROLS", 40.0, 30.0),
+        };
+        let height = 90.0 + self.rows() as f32 * spacing;
+        renderer.fill_rect(40.0, top - 30.0, SCREEN_WIDTH - 80.0, height, ui.panel);
+        draw_centered(renderer, title, top, 21.0, ui.title);
+
+        let row_top = |row: usize| top + 50.0 + row as f32 * spacing;
+        match self.screen {
+            Screen::Pause => {
+                for (row, item) in PAUSE_ITEMS.iter().enumerate() {
+                    draw_centered(renderer, item, row_top(row), 14.0, self.color(row, ui));
+                }
+            }
+            Screen::Options => {
+                let values = [settings.master_volume, settings.sfx_volume, settings.music_volume, settings.deadzone];
+                let camera = if settings.camera == CameraMode::Fixed { "FIXED" } else { "CHASE" };
+                for (row, item) in OPTION_ITEMS.iter().enumerate() {
+                    renderer.draw_text(item, 80.0, row_top(row), 14.0, self.color(row, ui));
+                    let text = match row {
+                        0..=3 => format!("< {:>3}% >", (values[row] * 100.0).round()),
+                        4 => format!("< {} >", settings.stick_shape.name()),
+                        5 => format!("< {} >", settings.control_schemes[self.player].name()),
+                        6 => format!("< {} >", camera),
+                        7 => format!("< {:>3}% >", (settings.rumble * 100.0).round()),
+                        8 => format!("< {} >", if settings.rumble_warnings { "ON" } else { "OFF" }),
+                        9 => format!("< {} X {} >", settings.arena_size()[0], settings.arena_size()[1]),
+                        10 => format!("< {} >", settings.theme().name),
+                        11 => format!("< {} >", settings.skin().name),
+                        12 => format!("< {:>3}% >", (settings.floatiness * 100.0).round()),
+                        13 => format!("< {} >", settings.floor_pattern.name()),
+                        _ => continue,
+                    };
+                    renderer.draw_text(&text, 380.0, row_top(row), 14.0, self.color(row, ui));
+                }
+            }
+            Screen::Controls => {
+                for (row, action) in ACTIONS.iter().enumerate() {
+                    renderer.draw_text(action.name(), 60.0, row_top(row), 12.0, self.color(row, ui));
+                    for (slot, binding) in settings.bindings.get(*action).iter().enumerate() {
+                        let selected = row == self.cursor && slot == self.column;
+                        let text = if selected && self.capture.is_some() {
+                            "PRESS...".to_owned()
+                        } else {
+                            binding.map_or("-".to_owned(), |b| b.name())
+                        };
+                        let color = if selected { ui.selected } else { ui.item };
+                        renderer.draw_text(&text, 220.0 + slot as f32 * 125.0, row_top(row), 12.0, color);
+                    }
+                }
+                for (i, item) in CONTROL_ITEMS.iter().enumerate() {
+                    let row = ACTIONS.len() + i;
+                    draw_centered(renderer, item, row_top(row), 14.0, self.color(row, ui));
+                }
+            }
+        }
+
+        render::end_overlay(renderer);
+    }
+
+    fn color(&self, row: usize, ui: &UiPalette) -> Vector4 {
+        if row == self.cursor { ui.selected } else { ui.item }
+    }
+}
+
+fn draw_centered<R: Renderer>(renderer: &mut R, text: &str, y: f32, size: f32, color: Vector4) {
+    let x = (SCREEN_WIDTH - font::text_width(text, size)) / 2.0;
+    renderer.draw_text(text, x, y, size, color);
+}
+
+// a box in the middle of the screen with a few lines of text, for messages that aren't a menu
+pub fn draw_prompt<R: Renderer>(renderer: &mut R, ui: &UiPalette, title: &str, lines: &[&str]) {
+    render::begin_overlay(renderer);
+
+    let top = 200.0 - lines.len() as f32 * 12.0;
+    renderer.fill_rect(40.0, top - 30.0, SCREEN_WIDTH - 80.0, 80.0 + lines.len() as f32 * 24.0, ui.panel);
+    draw_centered(renderer, title, top, 21.0, ui.title);
+    for (i, line) in lines.iter().enumerate() {
+        draw_centered(renderer, line, top + 44.0 + i as f32 * 24.0, 12.0, ui.item);
+    }
+
+    render::end_overlay(renderer);
+}
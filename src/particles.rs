@@ -0,0 +1,179 @@
+use dbsdk_rs::{math::{Vector3, Vector4}, vdp};
+
+use crate::animation::track::Lerp;
+use crate::geometry::cube::Cube;
+use crate::geometry::square::Square;
+use crate::geometry::weight::CENTER;
+use crate::rng::Rng;
+use crate::util::{vec3, vec3_from, vec4};
+
+#[derive(Clone, Copy)]
+pub enum ParticleShape {
+    Cube,
+    Quad, // flat, lying in the plane of the board
+}
+
+/*
+describes a burst of particles
+    distances are in cells and times are in vsync ticks
+    pairs are [min, max] for random ranges and [start, end] for values that change over a particle's life
+*/
+#[derive(Clone, Copy)]
+pub struct Emitter {
+    pub count: u16,
+    pub shape: ParticleShape,
+    pub direction: Vector3, // added to every particle's velocity
+    pub spread: Vector3,    // random velocity range on each axis
+    pub speed: [f32; 2],
+    pub gravity: Vector3,
+    pub drag: f32,          // fraction of velocity kept each tick
+    pub lifetime: [u16; 2],
+    pub size: [f32; 2],
+    pub color: [Vector4; 2],
+}
+
+#[derive(Clone, Copy)]
+struct Particle {
+    position: Vector3,
+    velocity: Vector3,
+    gravity: Vector3,
+    drag: f32,
+    age: u16,
+    lifetime: u16,
+    shape: ParticleShape,
+    size: [f32; 2],
+    color: [Vector4; 2],
+}
+
+// particles are only looks, so they have their own rng and never change where food lands
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    max: usize,
+    rng: Rng,
+}
+
+impl ParticleSystem {
+    pub fn new(max: usize, rng: Rng) -> ParticleSystem {
+        ParticleSystem {
+            particles: Vec::with_capacity(max),
+            max,
+            rng,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    pub fn emit(&mut self, emitter: &Emitter, origin: Vector3) {
+        let rng = &mut self.rng;
+        for _ in 0..emitter.count {
+            // oldest particles make room for new ones
+            if self.particles.len() >= self.max {
+                self.particles.remove(0);
+            }
+
+            let dir = vec3(
+                rng.random_f32() * 2.0 - 1.0,
+                rng.random_f32() * 2.0 - 1.0,
+                rng.random_f32() * 2.0 - 1.0
+            );
+            let speed = emitter.speed[0] + (emitter.speed[1] - emitter.speed[0]) * rng.random_f32();
+            let lifetime_range = emitter.lifetime[1].saturating_sub(emitter.lifetime[0]);
+            let lifetime = emitter.lifetime[0] + if lifetime_range > 0 { rng.random_single(lifetime_range) } else { 0 };
+
+            self.particles.push(Particle {
+                position: origin,
+                velocity: emitter.direction + dir * emitter.spread * speed,
+                gravity: emitter.gravity,
+                drag: emitter.drag,
+                age: 0,
+                lifetime,
+                shape: emitter.shape,
+                size: emitter.size,
+                color: emitter.color,
+            });
+        }
+    }
+
+    pub fn tick(&mut self) {
+        for p in self.particles.iter_mut() {
+            p.age += 1;
+            p.velocity = (p.velocity + p.gravity) * p.drag;
+            p.position = p.position + p.velocity;
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+    }
+
+    // size is the size of a cell, same as the rest of the board
+    pub fn tris(&self, other: &mut Vec<vdp::Vertex>, size: f32) {
+        for p in self.particles.iter() {
+            let t = p.age as f32 / p.lifetime as f32;
+            let half = Lerp::lerp(p.size[0], p.size[1], t) / 2.0;
+            let color = Vector4::lerp(p.color[0], p.color[1], t);
+
+            let from = (p.position - vec3_from(half)) * size;
+            let to = (p.position + vec3_from(half)) * size;
+
+            match p.shape {
+                ParticleShape::Cube => {
+                    let c = Cube::new(from, to, vec3_from(1.0), color, CENTER);
+                    other.append(&mut c.tris());
+                }
+                ParticleShape::Quad => {
+                    let z = p.position.z * size;
+                    let s = Square::new(vec3(from.x, from.y, z), vec3(to.x, to.y, z), vec3_from(1.0), color, CENTER);
+                    other.append(&mut s.tris());
+                }
+            }
+        }
+    }
+}
+
+// little red chunks flying out of the head
+pub fn food_eaten() -> Emitter {
+    Emitter {
+        count: 10,
+        shape: ParticleShape::Cube,
+        direction: vec3(0.0, 0.0, 0.06),
+        spread: vec3(1.0, 1.0, 0.5),
+        speed: [0.04, 0.09],
+        gravity: vec3(0.0, 0.0, -0.006),
+        drag: 0.96,
+        lifetime: [20, 35],
+        size: [0.18, 0.0],
+        color: [vec4(1.0, 0.2, 0.2, 1.0), vec4(1.0, 0.8, 0.3, 1.0)],
+    }
+}
+
+// the whole head comes apart
+pub fn death_explosion() -> Emitter {
+    Emitter {
+        count: 32,
+        shape: ParticleShape::Cube,
+        direction: vec3(0.0, 0.0, 0.1),
+        spread: vec3(1.0, 1.0, 0.8),
+        speed: [0.08, 0.2],
+        gravity: vec3(0.0, 0.0, -0.01),
+        drag: 0.97,
+        lifetime: [40, 70],
+        size: [0.3, 0.05],
+        color: [vec4(0.4, 1.0, 0.4, 1.0), vec4(0.1, 0.2, 0.1, 1.0)],
+    }
+}
+
+// a small ring of dust where new food lands
+pub fn food_spawn() -> Emitter {
+    Emitter {
+        count: 8,
+        shape: ParticleShape::Quad,
+        direction: vec3_from(0.0),
+        spread: vec3(1.0, 1.0, 0.0),
+        speed: [0.03, 0.05],
+        gravity: vec3_from(0.0),
+        drag: 0.9,
+        lifetime: [15, 25],
+        size: [0.15, 0.0],
+        color: [vec4(1.0, 0.6, 0.6, 1.0), vec4(0.3, 0.3, 0.3, 1.0)],
+    }
+}
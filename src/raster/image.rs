@@ -0,0 +1,585 @@
+use std::convert::TryInto;
+use std::io::{self, Write};
+
+use super::Rasterizer;
+
+/*
+image writers for the software rasterizer
+    ppm is trivial and easy to diff, png is what ends up in the repo since everything can show it
+    the png encoder is deliberately simple so the output for a given image is always byte for byte identical
+        deflate with the fixed huffman table, only matching against the previous pixel and the pixel above
+        that's plenty for renders that are mostly flat colors
+    snapshot tests still compare decoded pixels, not bytes, so the encoder can change (or an image can be
+    run through an optimizer) without every reference going stale
+        read_png handles any 8 bit RGB / RGBA non interlaced png, stored, fixed and dynamic deflate blocks
+*/
+
+// binary P6, alpha is dropped
+pub fn write_ppm<W: Write>(raster: &Rasterizer, out: &mut W) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", raster.width, raster.height)?;
+
+    let mut data = Vec::with_capacity(raster.width * raster.height * 3);
+    for c in raster.pixels() {
+        data.push(c.r);
+        data.push(c.g);
+        data.push(c.b);
+    }
+    out.write_all(&data)
+}
+
+// 8 bit RGBA
+pub fn write_png<W: Write>(raster: &Rasterizer, out: &mut W) -> io::Result<()> {
+    out.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'])?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(raster.width as u32).to_be_bytes());
+    header.extend_from_slice(&(raster.height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 6, 0, 0, 0]); // bit depth, RGBA, deflate, no filter method, no interlace
+    chunk(out, b"IHDR", &header)?;
+
+    // every scanline starts with filter type 0
+    let mut raw = Vec::with_capacity((raster.width * 4 + 1) * raster.height);
+    for y in 0..raster.height {
+        raw.push(0);
+        for x in 0..raster.width {
+            let c = raster.pixel(x, y);
+            raw.extend_from_slice(&[c.r, c.g, c.b, c.a]);
+        }
+    }
+    chunk(out, b"IDAT", &zlib(&raw, raster.width * 4 + 1))?;
+
+    chunk(out, b"IEND", &[])
+}
+
+// width, height and RGBA pixels row by row, None for anything it can't (or won't) read
+pub fn read_png(bytes: &[u8]) -> Option<(usize, usize, Vec<[u8; 4]>)> {
+    if bytes.get(..8)? != [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'] {
+        return None;
+    }
+
+    let mut header = None;
+    let mut compressed = Vec::new();
+    let mut rest = &bytes[8..];
+    loop {
+        let length = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+        let kind = rest.get(4..8)?;
+        let data = rest.get(8..8 + length)?;
+        rest = rest.get(12 + length..)?;
+
+        match kind {
+            b"IHDR" => header = Some(data),
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let header = header?;
+    if header.len() != 13 {
+        return None;
+    }
+    let width = u32::from_be_bytes(header[0..4].try_into().ok()?) as usize;
+    let height = u32::from_be_bytes(header[4..8].try_into().ok()?) as usize;
+    let channels = match (header[8], header[9], header[12]) {
+        (8, 2, 0) => 3,
+        (8, 6, 0) => 4,
+        _ => return None,
+    };
+
+    // 2 byte zlib header in front, adler32 behind
+    let raw = inflate(compressed.get(2..)?)?;
+    let stride = width * channels;
+    if raw.len() < (stride + 1) * height {
+        return None;
+    }
+
+    let mut pixels = Vec::with_capacity(width * height);
+    let mut previous = vec![0u8; stride];
+    let mut line = vec![0u8; stride];
+    for y in 0..height {
+        let row = &raw[y * (stride + 1)..(y + 1) * (stride + 1)];
+        for i in 0..stride {
+            let left = if i >= channels { line[i - channels] } else { 0 };
+            let above = previous[i];
+            let corner = if i >= channels { previous[i - channels] } else { 0 };
+            let predicted = match row[0] {
+                0 => 0,
+                1 => left,
+                2 => above,
+                3 => ((left as u16 + above as u16) / 2) as u8,
+                4 => paeth(left, above, corner),
+                _ => return None,
+            };
+            line[i] = row[1 + i].wrapping_add(predicted);
+        }
+
+        for p in line.chunks(channels) {
+            pixels.push([p[0], p[1], p[2], if channels == 4 { p[3] } else { 255 }]);
+        }
+        std::mem::swap(&mut line, &mut previous);
+    }
+
+    Some((width, height, pixels))
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+
+    let mut crc = Crc32::new();
+    crc.update(kind);
+    crc.update(data);
+    out.write_all(&crc.finish().to_be_bytes())
+}
+
+fn zlib(data: &[u8], stride: usize) -> Vec<u8> {
+    let mut bits = BitWriter::new();
+    bits.bytes(&[0x78, 0x01]);
+
+    // a single final block using the fixed huffman codes
+    bits.bits(1, 1);
+    bits.bits(1, 2);
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0);
+        for &distance in [4, stride].iter() {
+            if distance == 0 || distance > i {
+                continue;
+            }
+            let mut length = 0;
+            while length < 258 && i + length < data.len() && data[i + length] == data[i + length - distance] {
+                length += 1;
+            }
+            if length > best.0 {
+                best = (length, distance);
+            }
+        }
+
+        if best.0 >= 3 {
+            bits.length(best.0);
+            bits.distance(best.1);
+            i += best.0;
+        } else {
+            bits.literal(data[i] as u16);
+            i += 1;
+        }
+    }
+    bits.literal(256);
+
+    let mut out = bits.finish();
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut bits = BitReader::new(data);
+    let mut out = Vec::new();
+
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => {
+                bits.align();
+                let length = bits.bits(16)? as usize;
+                if bits.bits(16)? as usize != !length & 0xffff {
+                    return None;
+                }
+                for _ in 0..length {
+                    out.push(bits.bits(8)? as u8);
+                }
+            }
+            1 => {
+                let mut lengths = [0u8; 288 + 30];
+                for (i, l) in lengths.iter_mut().enumerate() {
+                    *l = match i {
+                        0..=143 => 8,
+                        144..=255 => 9,
+                        256..=279 => 7,
+                        280..=287 => 8,
+                        _ => 5,
+                    };
+                }
+                let literals = Huffman::new(&lengths[..288])?;
+                let distances = Huffman::new(&lengths[288..])?;
+                block(&mut bits, &mut out, &literals, &distances)?;
+            }
+            2 => {
+                let literal_count = bits.bits(5)? as usize + 257;
+                let distance_count = bits.bits(5)? as usize + 1;
+                let code_count = bits.bits(4)? as usize + 4;
+
+                let mut code_lengths = [0u8; 19];
+                for &i in CODE_ORDER.iter().take(code_count) {
+                    code_lengths[i] = bits.bits(3)? as u8;
+                }
+                let codes = Huffman::new(&code_lengths)?;
+
+                let mut lengths = Vec::with_capacity(literal_count + distance_count);
+                while lengths.len() < literal_count + distance_count {
+                    let (value, repeat) = match codes.decode(&mut bits)? {
+                        symbol @ 0..=15 => (symbol as u8, 1),
+                        16 => (*lengths.last()?, 3 + bits.bits(2)?),
+                        17 => (0, 3 + bits.bits(3)?),
+                        _ => (0, 11 + bits.bits(7)?),
+                    };
+                    for _ in 0..repeat {
+                        lengths.push(value);
+                    }
+                }
+                if lengths.len() != literal_count + distance_count {
+                    return None;
+                }
+
+                let literals = Huffman::new(&lengths[..literal_count])?;
+                let distances = Huffman::new(&lengths[literal_count..])?;
+                block(&mut bits, &mut out, &literals, &distances)?;
+            }
+            _ => return None,
+        }
+
+        if last {
+            return Some(out);
+        }
+    }
+}
+
+fn block(bits: &mut BitReader, out: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Option<()> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        if symbol < 256 {
+            out.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Some(());
+        }
+
+        let (_, base, extra) = *LENGTHS.get(symbol - 257)?;
+        let length = base + bits.bits(extra)? as usize;
+        let (_, base, extra) = *DISTANCES.get(distances.decode(bits)? as usize)?;
+        let distance = base + bits.bits(extra)? as usize;
+        if distance > out.len() {
+            return None;
+        }
+        for _ in 0..length {
+            out.push(out[out.len() - distance]);
+        }
+    }
+}
+
+// order the code length code lengths are stored in, RFC 1951 3.2.7
+static CODE_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+// canonical huffman table, how many codes there are of each length and the symbols sorted by code
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Option<Huffman> {
+        let mut counts = [0u16; 16];
+        for &l in lengths {
+            *counts.get_mut(l as usize)? += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; 16];
+        for l in 1..15 {
+            offsets[l + 1] = offsets[l] + counts[l];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &l) in lengths.iter().enumerate() {
+            if l != 0 {
+                symbols[offsets[l as usize] as usize] = symbol as u16;
+                offsets[l as usize] += 1;
+            }
+        }
+
+        Some(Huffman {
+            counts,
+            symbols,
+        })
+    }
+
+    // walks the code one bit at a time, codes of each length are consecutive numbers
+    fn decode(&self, bits: &mut BitReader) -> Option<u16> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for &count in self.counts.iter().skip(1) {
+            code |= bits.bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return self.symbols.get((index + code - first) as usize).cloned();
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    current: u32,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader {
+            data,
+            position: 0,
+            current: 0,
+            count: 0,
+        }
+    }
+
+    fn bits(&mut self, count: u32) -> Option<u32> {
+        while self.count < count {
+            self.current |= (*self.data.get(self.position)? as u32) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+        let value = self.current & ((1u64 << count) - 1) as u32;
+        self.current = if count == 32 { 0 } else { self.current >> count };
+        self.count -= count;
+        Some(value)
+    }
+
+    fn align(&mut self) {
+        self.current = 0;
+        self.count = 0;
+    }
+}
+
+// deflate packs bits starting from the least significant, huffman codes themselves go most significant first
+struct BitWriter {
+    out: Vec<u8>,
+    current: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter {
+            out: Vec::new(),
+            current: 0,
+            count: 0,
+        }
+    }
+
+    fn bits(&mut self, value: u32, count: u32) {
+        self.current |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.out.push(self.current as u8);
+            self.current >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.bits(b as u32, 8);
+        }
+    }
+
+    fn code(&mut self, code: u32, count: u32) {
+        let mut reversed = 0;
+        for i in 0..count {
+            reversed |= ((code >> i) & 1) << (count - 1 - i);
+        }
+        self.bits(reversed, count);
+    }
+
+    // fixed literal/length table from RFC 1951 3.2.6
+    fn literal(&mut self, value: u16) {
+        let value = value as u32;
+        match value {
+            0..=143 => self.code(0x30 + value, 8),
+            144..=255 => self.code(0x190 + value - 144, 9),
+            256..=279 => self.code(value - 256, 7),
+            _ => self.code(0xc0 + value - 280, 8),
+        }
+    }
+
+    fn length(&mut self, length: usize) {
+        let (code, base, extra) = LENGTHS.iter().cloned().rfind(|l| l.1 <= length).unwrap();
+        self.literal(code);
+        self.bits((length - base) as u32, extra);
+    }
+
+    fn distance(&mut self, distance: usize) {
+        let (code, base, extra) = DISTANCES.iter().cloned().rfind(|d| d.1 <= distance).unwrap();
+        self.code(code as u32, 5);
+        self.bits((distance - base) as u32, extra);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.current as u8);
+        }
+        self.out
+    }
+}
+
+// (code, base, extra bits)
+static LENGTHS: [(u16, usize, u32); 29] = [
+    (257, 3, 0), (258, 4, 0), (259, 5, 0), (260, 6, 0), (261, 7, 0), (262, 8, 0), (263, 9, 0), (264, 10, 0),
+    (265, 11, 1), (266, 13, 1), (267, 15, 1), (268, 17, 1),
+    (269, 19, 2), (270, 23, 2), (271, 27, 2), (272, 31, 2),
+    (273, 35, 3), (274, 43, 3), (275, 51, 3), (276, 59, 3),
+    (277, 67, 4), (278, 83, 4), (279, 99, 4), (280, 115, 4),
+    (281, 131, 5), (282, 163, 5), (283, 195, 5), (284, 227, 5),
+    (285, 258, 0),
+];
+
+static DISTANCES: [(u16, usize, u32); 30] = [
+    (0, 1, 0), (1, 2, 0), (2, 3, 0), (3, 4, 0), (4, 5, 1), (5, 7, 1), (6, 9, 2), (7, 13, 2),
+    (8, 17, 3), (9, 25, 3), (10, 33, 4), (11, 49, 4), (12, 65, 5), (13, 97, 5), (14, 129, 6), (15, 193, 6),
+    (16, 257, 7), (17, 385, 7), (18, 513, 8), (19, 769, 8), (20, 1025, 9), (21, 1537, 9), (22, 2049, 10), (23, 3073, 10),
+    (24, 4097, 11), (25, 6145, 11), (26, 8193, 12), (27, 12289, 12), (28, 16385, 13), (29, 24577, 13),
+];
+
+fn adler32(data: &[u8]) -> u32 {
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+struct Crc32 {
+    table: [u32; 256],
+    value: u32,
+}
+
+impl Crc32 {
+    fn new() -> Crc32 {
+        let mut table = [0u32; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 == 1 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+            }
+            *entry = c;
+        }
+        Crc32 {
+            table,
+            value: 0xffffffff,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.value = self.table[((self.value ^ byte as u32) & 0xff) as usize] ^ (self.value >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.value ^ 0xffffffff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dbsdk_rs::vdp::Color32;
+
+    #[test]
+    fn round_trip() {
+        let mut raster = Rasterizer::new(7, 5);
+        for (i, c) in raster.color.iter_mut().enumerate() {
+            let i = i as u8;
+            *c = match i % 3 {
+                0 => Color32::new(10, 20, 30, 255),
+                _ => Color32::new(i * 7, 255 - i, i.wrapping_mul(i), 128 + i),
+            };
+        }
+
+        let mut png = Vec::new();
+        write_png(&raster, &mut png).unwrap();
+        let (width, height, pixels) = read_png(&png).unwrap();
+
+        assert!(width == 7 && height == 5);
+        assert!(pixels.len() == raster.pixels().len());
+        for (p, c) in pixels.iter().zip(raster.pixels()) {
+            assert!(*p == [c.r, c.g, c.b, c.a]);
+        }
+    }
+
+    // 4x5 RGB written by another encoder, row y uses filter type y
+    #[test]
+    fn filters() {
+        let png = [
+            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x05, 0x08, 0x02, 0x00, 0x00, 0x00, 0xed, 0xcf, 0xda,
+            0x8c, 0x00, 0x00, 0x00, 0x41, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x60, 0x60, 0xe0, 0xb6,
+            0x61, 0xe0, 0xae, 0x60, 0xe0, 0xde, 0xc2, 0xc0, 0xcd, 0xc8, 0x60, 0x04, 0xe4, 0xa8, 0x42, 0x10,
+            0x13, 0x83, 0x11, 0x03, 0x83, 0x91, 0x2a, 0x83, 0x91, 0x17, 0x83, 0x51, 0x3e, 0x33, 0x43, 0x0a,
+            0x9b, 0x9c, 0xa4, 0x97, 0x9c, 0x64, 0xac, 0x9c, 0x64, 0x3e, 0x0b, 0x48, 0x86, 0x41, 0x95, 0x81,
+            0xc1, 0x8b, 0x81, 0x61, 0x0a, 0x00, 0xf8, 0xcb, 0x07, 0xff, 0x27, 0xf5, 0xb4, 0x3b, 0x00, 0x00,
+            0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+        ];
+        let (width, height, pixels) = read_png(&png).unwrap();
+
+        assert!(width == 4 && height == 5);
+        for y in 0..5 {
+            for x in 0..4 {
+                let expected = [(x * 60) as u8, (y * 50) as u8, ((x * y * 37 + 11) % 256) as u8, 255];
+                assert!(pixels[y * 4 + x] == expected, "pixel {} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn dynamic_and_stored_blocks() {
+        // zlib -9 picks a dynamic huffman block for this
+        let dynamic = [
+            0x78, 0xda, 0x0d, 0xc5, 0xc1, 0x11, 0x00, 0x21, 0x08, 0x03, 0xc0, 0x08, 0x71, 0x82, 0xc0, 0x61,
+            0xff, 0xdd, 0x7a, 0xfb, 0x59, 0xc4, 0x18, 0x29, 0xff, 0x12, 0x07, 0x70, 0x3a, 0x8a, 0xf5, 0x1f,
+            0x1b, 0xb3, 0xaf, 0xa2, 0x67, 0x3a, 0x2c, 0xc1, 0xaa, 0x93, 0x5c, 0x82, 0x94, 0x95, 0xb2, 0xb2,
+            0xbe, 0xe0, 0xea, 0x07, 0x32, 0x0b, 0x01, 0xa4,
+        ];
+        let expected: Vec<u8> = (0..64usize).map(|i| ((i * i * 7 + i) % 251 % 16) as u8).collect();
+        assert!(inflate(&dynamic[2..]) == Some(expected));
+
+        // non final stored block followed by a final fixed block holding only the end code
+        let stored = [0x00, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c', 0x03, 0x00];
+        assert!(inflate(&stored) == Some(b"abc".to_vec()));
+    }
+
+    #[test]
+    fn garbage() {
+        let mut png = Vec::new();
+        write_png(&Rasterizer::new(3, 3), &mut png).unwrap();
+
+        assert!(read_png(&[]).is_none());
+        assert!(read_png(&png[..png.len() / 2]).is_none());
+        let mut wrong = png.clone();
+        wrong[0] = 0;
+        assert!(read_png(&wrong).is_none());
+    }
+}
@@ -0,0 +1,259 @@
+/*
+software rasterizer
+    mirrors the parts of vdp that the game uses so scenes can be rendered on a host machine, for example to
+    compare against golden images in CI without running the DreamBox emulator
+
+    vertices are expected in clip space, exactly what vdp::draw_geometry receives after the SIMD transform
+    depth is z/w in [0,1] like the console, clear it to 1.0 and use Compare::LessOrEqual to match the game
+*/
+pub mod image;
+
+use dbsdk_rs::math::{Matrix4x4, Vector4};
+use dbsdk_rs::vdp::{self, Color32, Compare, Topology};
+
+use crate::util::vec4;
+
+pub struct Rasterizer {
+    pub width: usize,
+    pub height: usize,
+    color: Vec<Color32>,
+    depth: Vec<f32>,
+    depth_write: bool,
+    depth_func: Compare,
+}
+
+// a vertex after the perspective divide, x and y are in pixels
+#[derive(Clone, Copy)]
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    color: Vector4, // premultiplied by inv_w for perspective correct interpolation
+}
+
+impl Rasterizer {
+    pub fn new(width: usize, height: usize) -> Rasterizer {
+        Rasterizer {
+            width,
+            height,
+            color: vec![Color32::new(0, 0, 0, 255); width * height],
+            depth: vec![1.0; width * height],
+            depth_write: true,
+            depth_func: Compare::LessOrEqual,
+        }
+    }
+
+    pub fn clear_color(&mut self, color: Color32) {
+        for c in self.color.iter_mut() {
+            *c = color;
+        }
+    }
+
+    pub fn clear_depth(&mut self, depth: f32) {
+        for d in self.depth.iter_mut() {
+            *d = depth;
+        }
+    }
+
+    pub fn depth_write(&mut self, enable: bool) {
+        self.depth_write = enable;
+    }
+
+    pub fn depth_func(&mut self, compare: Compare) {
+        self.depth_func = compare;
+    }
+
+    pub fn pixels(&self) -> &[Color32] {
+        &self.color
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color32 {
+        self.color[y * self.width + x]
+    }
+
+    pub fn draw_geometry(&mut self, topology: Topology, vertex_data: &[vdp::Vertex]) {
+        match topology {
+            Topology::TriangleList => {
+                for tri in vertex_data.chunks(3) {
+                    if tri.len() == 3 {
+                        self.triangle(tri[0], tri[1], tri[2]);
+                    }
+                }
+            }
+            Topology::TriangleStrip => {
+                for i in 2..vertex_data.len() {
+                    self.triangle(vertex_data[i - 2], vertex_data[i - 1], vertex_data[i]);
+                }
+            }
+            Topology::LineList => {
+                for line in vertex_data.chunks(2) {
+                    if line.len() == 2 {
+                        self.line(line[0], line[1]);
+                    }
+                }
+            }
+            Topology::LineStrip => {
+                for i in 1..vertex_data.len() {
+                    self.line(vertex_data[i - 1], vertex_data[i]);
+                }
+            }
+        }
+    }
+
+    fn depth_test(&self, index: usize, z: f32) -> bool {
+        let d = self.depth[index];
+        match self.depth_func {
+            Compare::Never => false,
+            Compare::Less => z < d,
+            Compare::Equal => z == d,
+            Compare::LessOrEqual => z <= d,
+            Compare::Greater => z > d,
+            Compare::NotEqual => z != d,
+            Compare::GreaterOrEqual => z >= d,
+            Compare::Always => true,
+        }
+    }
+
+    fn plot(&mut self, x: usize, y: usize, z: f32, color: Vector4) {
+        if !(0.0..=1.0).contains(&z) {
+            return
+        }
+        let index = y * self.width + x;
+        if !self.depth_test(index, z) {
+            return
+        }
+        if self.depth_write {
+            self.depth[index] = z;
+        }
+        self.color[index] = to_color32(color);
+    }
+
+    fn to_screen(&self, v: &vdp::Vertex) -> ScreenVertex {
+        let inv_w = 1.0 / v.position.w;
+        ScreenVertex {
+            x: (v.position.x * inv_w * 0.5 + 0.5) * self.width as f32,
+            y: (0.5 - v.position.y * inv_w * 0.5) * self.height as f32,
+            z: v.position.z * inv_w,
+            inv_w,
+            color: v.color * inv_w,
+        }
+    }
+
+    fn triangle(&mut self, a: vdp::Vertex, b: vdp::Vertex, c: vdp::Vertex) {
+        // clip against the near plane (z >= 0) then fan out whatever polygon is left
+        let poly = clip_near(&[a, b, c]);
+        if poly.len() < 3 {
+            return
+        }
+        let screen: Vec<ScreenVertex> = poly.iter().map(|v| self.to_screen(v)).collect();
+        for i in 1..(screen.len() - 1) {
+            self.fill(screen[0], screen[i], screen[i + 1]);
+        }
+    }
+
+    fn fill(&mut self, a: ScreenVertex, b: ScreenVertex, c: ScreenVertex) {
+        let area = edge(&a, &b, c.x, c.y);
+        if area == 0.0 {
+            return
+        }
+
+        let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as usize;
+        let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as usize;
+        let max_x = (a.x.max(b.x).max(c.x).ceil() as usize).min(self.width);
+        let max_y = (a.y.max(b.y).max(c.y).ceil() as usize).min(self.height);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                // sample at the pixel center
+                let px = x as f32 + 0.5;
+                let py = y as f32 + 0.5;
+
+                // both windings are drawn, culling is never turned on by the game
+                let w0 = edge(&b, &c, px, py) / area;
+                let w1 = edge(&c, &a, px, py) / area;
+                let w2 = edge(&a, &b, px, py) / area;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+
+                let z = a.z * w0 + b.z * w1 + c.z * w2;
+                let inv_w = a.inv_w * w0 + b.inv_w * w1 + c.inv_w * w2;
+                let color = (a.color * w0 + b.color * w1 + c.color * w2) / inv_w;
+                self.plot(x, y, z, color);
+            }
+        }
+    }
+
+    fn line(&mut self, a: vdp::Vertex, b: vdp::Vertex) {
+        if a.position.z < 0.0 || b.position.z < 0.0 {
+            return
+        }
+        let a = self.to_screen(&a);
+        let b = self.to_screen(&b);
+
+        let steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.0) as usize;
+        for i in 0..(steps + 1) {
+            let t = i as f32 / steps as f32;
+            let x = a.x + (b.x - a.x) * t;
+            let y = a.y + (b.y - a.y) * t;
+            if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+                continue;
+            }
+            let z = a.z + (b.z - a.z) * t;
+            let inv_w = a.inv_w + (b.inv_w - a.inv_w) * t;
+            let color = (a.color + (b.color - a.color) * t) / inv_w;
+            self.plot(x as usize, y as usize, z, color);
+        }
+    }
+}
+
+// cpu version of Matrix4x4::transform_vertex_simd for the vertex positions
+pub fn transform(tris: &mut [vdp::Vertex], matrix: &Matrix4x4) {
+    for vert in tris.iter_mut() {
+        vert.position = *matrix * vert.position;
+    }
+}
+
+fn edge(a: &ScreenVertex, b: &ScreenVertex, x: f32, y: f32) -> f32 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+fn clip_near(poly: &[vdp::Vertex]) -> Vec<vdp::Vertex> {
+    let mut out = Vec::with_capacity(poly.len() + 1);
+
+    for i in 0..poly.len() {
+        let cur = poly[i];
+        let next = poly[(i + 1) % poly.len()];
+        let cur_in = cur.position.z >= 0.0;
+        let next_in = next.position.z >= 0.0;
+
+        if cur_in {
+            out.push(cur);
+        }
+        if cur_in != next_in {
+            let t = cur.position.z / (cur.position.z - next.position.z);
+            let mut v = cur;
+            v.position = cur.position + (next.position - cur.position) * t;
+            v.color = cur.color + (next.color - cur.color) * t;
+            out.push(v);
+        }
+    }
+
+    out
+}
+
+fn to_color32(color: Vector4) -> Color32 {
+    let c = vec4(
+        color.x.clamp(0.0, 1.0),
+        color.y.clamp(0.0, 1.0),
+        color.z.clamp(0.0, 1.0),
+        color.w.clamp(0.0, 1.0)
+    );
+    Color32::new(
+        (c.x * 255.0 + 0.5) as u8,
+        (c.y * 255.0 + 0.5) as u8,
+        (c.z * 255.0 + 0.5) as u8,
+        (c.w * 255.0 + 0.5) as u8
+    )
+}
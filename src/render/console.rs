@@ -0,0 +1,51 @@
+use dbsdk_rs::field_offset::offset_of;
+use dbsdk_rs::math::Matrix4x4;
+use dbsdk_rs::vdp::{self, Color32, Compare};
+
+use super::Renderer;
+
+pub struct VdpRenderer {
+    camera: Vec<Matrix4x4>,
+}
+
+impl Default for VdpRenderer {
+    fn default() -> VdpRenderer {
+        VdpRenderer::new()
+    }
+}
+
+impl VdpRenderer {
+    pub fn new() -> VdpRenderer {
+        VdpRenderer {
+            camera: Vec::new(),
+        }
+    }
+}
+
+impl Renderer for VdpRenderer {
+    fn clear(&mut self, color: Color32, depth: f32) {
+        vdp::clear_color(color);
+        vdp::clear_depth(depth);
+    }
+
+    fn set_camera(&mut self, camera: &[Matrix4x4]) {
+        self.camera = camera.to_vec();
+    }
+
+    fn set_depth(&mut self, write: bool, compare: Compare) {
+        vdp::depth_write(write);
+        vdp::depth_func(compare);
+    }
+
+    fn submit(&mut self, tris: &mut Vec<vdp::Vertex>) {
+        Matrix4x4::load_identity_simd();
+
+        for matrix in self.camera.iter() {
+            Matrix4x4::mul_simd(matrix);
+        }
+
+        Matrix4x4::transform_vertex_simd(tris, offset_of!(vdp::Vertex => position));
+
+        vdp::draw_geometry(vdp::Topology::TriangleList, tris);
+    }
+}
@@ -0,0 +1,61 @@
+/*
+everything that draws goes through a Renderer so the game isn't tied to the console
+    console.rs is the real thing, vdp and the SIMD matrix register
+    software.rs draws into the software rasterizer, for rendering on a host machine
+    recording.rs keeps a list of every call, or throws everything away, for tests and tools
+
+the camera is a list of matrices multiplied in order, same as draw::camera_matrices
+tris are submitted untransformed, backends are free to transform them in place
+*/
+pub mod console;
+pub mod software;
+pub mod recording;
+
+use dbsdk_rs::math::{Matrix4x4, Vector4};
+use dbsdk_rs::vdp::{self, Color32, Compare};
+
+use font;
+use geometry::square::Square;
+use geometry::weight::CENTER;
+use util::{vec3, vec3_from};
+
+// text is positioned in screen pixels, 640x480 from the top left
+pub static SCREEN_WIDTH: f32 = 640.0;
+pub static SCREEN_HEIGHT: f32 = 480.0;
+
+pub trait Renderer {
+    fn clear(&mut self, color: Color32, depth: f32);
+    fn set_camera(&mut self, camera: &[Matrix4x4]);
+    fn set_depth(&mut self, write: bool, compare: Compare);
+    fn submit(&mut self, tris: &mut Vec<vdp::Vertex>);
+
+    // size is the height of a character in pixels, this changes the camera so draw text after everything else
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, size: f32, color: Vector4) {
+        let mut tris = font::text_tris(text, x, y, size, color);
+        self.set_camera(&[screen_camera()]);
+        self.submit(&mut tris);
+    }
+
+    // a flat rectangle in screen pixels, x, y is the top left
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Vector4) {
+        let square = Square::new(vec3(x, y, 0.0), vec3(x + width, y + height, 0.0), vec3_from(1.0), color, CENTER);
+        let mut tris = square.tris();
+        self.set_camera(&[screen_camera()]);
+        self.submit(&mut tris);
+    }
+}
+
+// for drawing 2d things over the board, everything drawn after this lands on top in draw order
+pub fn begin_overlay<R: Renderer>(renderer: &mut R) {
+    renderer.set_depth(false, Compare::Always);
+}
+
+// back to the depth state the board is drawn with
+pub fn end_overlay<R: Renderer>(renderer: &mut R) {
+    renderer.set_depth(true, Compare::LessOrEqual);
+}
+
+// maps screen pixels straight to clip space
+pub fn screen_camera() -> Matrix4x4 {
+    Matrix4x4::projection_ortho(0.0, SCREEN_WIDTH, 0.0, SCREEN_HEIGHT, 0.0, 1.0)
+}
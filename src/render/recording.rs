@@ -0,0 +1,152 @@
+use dbsdk_rs::math::{Matrix4x4, Vector4};
+use dbsdk_rs::vdp::{self, Color32, Compare};
+
+use super::Renderer;
+
+pub enum Command {
+    Clear(Color32, f32),
+    Camera(Vec<Matrix4x4>),
+    Depth(bool, Compare),
+    Triangles(Vec<vdp::Vertex>),
+    Text(String, f32, f32, f32, Vector4),
+}
+
+// keeps every call, tris are stored untransformed
+pub struct RecordingRenderer {
+    pub commands: Vec<Command>,
+}
+
+impl Default for RecordingRenderer {
+    fn default() -> RecordingRenderer {
+        RecordingRenderer::new()
+    }
+}
+
+impl RecordingRenderer {
+    pub fn new() -> RecordingRenderer {
+        RecordingRenderer {
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.commands.iter().map(|c| match c {
+            Command::Triangles(tris) => tris.len() / 3,
+            _ => 0,
+        }).sum()
+    }
+
+    pub fn text(&self) -> Vec<&str> {
+        self.commands.iter().filter_map(|c| match c {
+            Command::Text(text, _, _, _, _) => Some(text.as_str()),
+            _ => None,
+        }).collect()
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn clear(&mut self, color: Color32, depth: f32) {
+        self.commands.push(Command::Clear(color, depth));
+    }
+
+    fn set_camera(&mut self, camera: &[Matrix4x4]) {
+        self.commands.push(Command::Camera(camera.to_vec()));
+    }
+
+    fn set_depth(&mut self, write: bool, compare: Compare) {
+        self.commands.push(Command::Depth(write, compare));
+    }
+
+    fn submit(&mut self, tris: &mut Vec<vdp::Vertex>) {
+        self.commands.push(Command::Triangles(tris.clone()));
+    }
+
+    // recorded as text rather than the glyph tris so it's easy to check what was written
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, size: f32, color: Vector4) {
+        self.commands.push(Command::Text(text.to_owned(), x, y, size, color));
+    }
+}
+
+// draws nothing
+pub struct NullRenderer;
+
+impl Renderer for NullRenderer {
+    fn clear(&mut self, _color: Color32, _depth: f32) {}
+    fn set_camera(&mut self, _camera: &[Matrix4x4]) {}
+    fn set_depth(&mut self, _write: bool, _compare: Compare) {}
+    fn submit(&mut self, _tris: &mut Vec<vdp::Vertex>) {}
+    fn draw_text(&mut self, _text: &str, _x: f32, _y: f32, _size: f32, _color: Vector4) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use menu::Menu;
+    use rng::Rng;
+    use settings::Settings;
+    use snake::Game;
+    use theme;
+
+    fn game() -> Game {
+        let mut game = Game::with_rng(12, 12, 3, 4, 8, Rng::from_seeds([3, 7]));
+        for _ in 0..40 {
+            game.tick();
+        }
+        game
+    }
+
+    // clear, then the backdrop and the board each under their own camera, nothing else
+    #[test]
+    fn game_draw() {
+        let mut renderer = RecordingRenderer::new();
+        game().draw(&mut renderer);
+
+        let background = theme::CLASSIC.background;
+        match &renderer.commands[0] {
+            Command::Clear(color, depth) => {
+                assert!((color.r, color.g, color.b) == (background.r, background.g, background.b));
+                assert!(*depth == 1.0);
+            }
+            _ => panic!("game draw should start with a clear"),
+        }
+        assert!(renderer.commands.len() == 5);
+        assert!(matches!(renderer.commands[1], Command::Camera(_)));
+        assert!(matches!(renderer.commands[2], Command::Triangles(_)));
+        assert!(matches!(renderer.commands[3], Command::Camera(_)));
+        assert!(renderer.text().is_empty());
+
+        // the last batch is the board exactly as scene builds it, untransformed
+        let (board, _) = game().scene();
+        match &renderer.commands[4] {
+            Command::Triangles(tris) => {
+                assert!(tris.len() == board.len());
+                assert!(tris.iter().zip(board.iter()).all(|(a, b)| a.position.x == b.position.x && a.position.y == b.position.y && a.position.z == b.position.z));
+            }
+            _ => panic!("board should be drawn last"),
+        }
+        assert!(renderer.triangle_count() > board.len() / 3);
+
+        // throwing it all away shouldn't care either
+        game().draw(&mut NullRenderer);
+    }
+
+    #[test]
+    fn menu_draw() {
+        let settings = Settings::new();
+        let mut menu = Menu::new();
+
+        let mut renderer = RecordingRenderer::new();
+        menu.draw(&mut renderer, &settings);
+        assert!(renderer.commands.is_empty());
+
+        menu.show();
+        menu.draw(&mut renderer, &settings);
+        assert!(renderer.text() == ["PAUSED", "RESUME", "OPTIONS", "CONTROLS", "QUICK SAVE", "QUICK LOAD", "RESTART"]);
+
+        // an overlay, so it turns the depth test off and puts it back afterwards
+        assert!(matches!(renderer.commands.first(), Some(Command::Depth(false, Compare::Always))));
+        assert!(matches!(renderer.commands.last(), Some(Command::Depth(true, Compare::LessOrEqual))));
+        // the panel behind the text is a single rect
+        assert!(renderer.triangle_count() == 2);
+    }
+}
@@ -0,0 +1,45 @@
+use dbsdk_rs::math::Matrix4x4;
+use dbsdk_rs::vdp::{self, Color32, Compare, Topology};
+
+use raster::{self, Rasterizer};
+use util::mat_mul;
+
+use super::Renderer;
+
+pub struct SoftwareRenderer {
+    pub raster: Rasterizer,
+    camera: Matrix4x4,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: usize, height: usize) -> SoftwareRenderer {
+        SoftwareRenderer {
+            raster: Rasterizer::new(width, height),
+            camera: Matrix4x4::identity(),
+        }
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn clear(&mut self, color: Color32, depth: f32) {
+        self.raster.clear_color(color);
+        self.raster.clear_depth(depth);
+    }
+
+    fn set_camera(&mut self, camera: &[Matrix4x4]) {
+        self.camera = Matrix4x4::identity();
+        for matrix in camera.iter() {
+            self.camera = mat_mul(&self.camera, matrix);
+        }
+    }
+
+    fn set_depth(&mut self, write: bool, compare: Compare) {
+        self.raster.depth_write(write);
+        self.raster.depth_func(compare);
+    }
+
+    fn submit(&mut self, tris: &mut Vec<vdp::Vertex>) {
+        raster::transform(tris, &self.camera);
+        self.raster.draw_geometry(Topology::TriangleList, tris);
+    }
+}
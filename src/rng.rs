@@ -13,13 +13,19 @@ sm64 rng
 https://youtu.be/q15yNrJHOak?t=292
 
 note
-    sm64's rng implementation will return 2 u8 numbers, which is why `next` and `random` return [u8; 2]
+    sm64's rng implementation will return 2 u8 numbers, which is why `next_pair` and `random` return [u8; 2]
     the second number can be discarded if you only need 1 number
 */
 pub struct Rng {
     seeds: [u8; 2]
 }
 
+impl Default for Rng {
+    fn default() -> Rng {
+        Rng::new()
+    }
+}
+
 impl Rng {
     pub fn new() -> Rng {
         Rng {
@@ -27,8 +33,20 @@ impl Rng {
         }
     }
 
+    // fixed seeds, for anything that needs to be reproducible or runs off the console
+    pub fn from_seeds(seeds: [u8; 2]) -> Rng {
+        Rng {
+            seeds,
+        }
+    }
+
+    // where the sequence is up to, from_seeds with these carries on from here
+    pub fn current_seeds(&self) -> [u8; 2] {
+        self.seeds
+    }
+
     // 2 u8 numbers
-    pub fn next(&mut self) -> [u8; 2] {
+    pub fn next_pair(&mut self) -> [u8; 2] {
         let mut num: [u8; 2] = [0, 0];
 
         self.tick();
@@ -41,27 +59,32 @@ impl Rng {
 
     // 2 u8 numbers between 0 and max
     pub fn random(&mut self, max: u8) -> [u8; 2] {
-        let mut num = self.next();
+        let mut num = self.next_pair();
 
-        num[0] = num[0] % max;
-        num[1] = num[1] % max;
+        num[0] %= max;
+        num[1] %= max;
 
         num
     }
 
     // combine 2 u8 numbers into 1 u16 number between 0 and max
     pub fn random_single(&mut self, max: u16) -> u16 {
-        let num = self.next();
+        let num = self.next_pair();
         (((num[0] as u16) << 8) | num[1] as u16) % max
     }
 
     // combine 4 u8 numbers into 1 u32 number between 0 and max
     pub fn random_single_u32(&mut self, max: u32) -> u32 {
-        let num0 = self.next();
-        let num1 = self.next();
+        let num0 = self.next_pair();
+        let num1 = self.next_pair();
         (((num0[0] as u32) << 24) | ((num0[1] as u32) << 16) | ((num1[0] as u32) << 8) | num1[1] as u32) % max
     }
 
+    // float between 0 and 1
+    pub fn random_f32(&mut self) -> f32 {
+        self.random_single(u16::MAX) as f32 / (u16::MAX as f32)
+    }
+
     fn tick(&mut self) {
         self.seeds[0] = self.seeds[0].wrapping_mul(5).wrapping_add(1);
 
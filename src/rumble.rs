@@ -0,0 +1,237 @@
+use dbsdk_rs::gamepad::Gamepad;
+
+use snake::Event;
+
+// anything that can rumble, the console's gamepad only has on and off
+pub trait Motor {
+    fn set_rumble(&self, on: bool);
+}
+
+impl Motor for Gamepad {
+    fn set_rumble(&self, on: bool) {
+        Gamepad::set_rumble(self, on);
+    }
+}
+
+// for a keyboard, or a controller without a motor
+pub struct NoMotor;
+
+impl Motor for NoMotor {
+    fn set_rumble(&self, _on: bool) {}
+}
+
+// strength is 0 to 1, ticks is how long it lasts in vsync ticks
+#[derive(Clone, Copy)]
+pub struct Pulse {
+    pub strength: f32,
+    pub ticks: u32,
+}
+
+pub static EAT: Pulse = Pulse { strength: 0.5, ticks: 8 };
+pub static DEATH: Pulse = Pulse { strength: 1.0, ticks: 30 };
+pub static WARNING: Pulse = Pulse { strength: 0.3, ticks: 6 };
+
+// the motor is switched on for part of every PERIOD ticks, that's how anything under full strength is done
+static PERIOD: u32 = 4;
+
+/*
+rumble feedback for one player
+    a new pulse replaces the current one if it's at least as strong, otherwise it's dropped
+    intensity scales every pulse, 0 turns rumble off
+    warnings are for when the next move would kill the snake, given once each time that starts being true
+*/
+pub struct Rumble {
+    pub intensity: f32,
+    pub warnings: bool,
+    pulse: Option<Pulse>,
+    tick: u32,
+    danger: bool,
+    on: bool,
+}
+
+impl Rumble {
+    pub fn new(intensity: f32, warnings: bool) -> Rumble {
+        Rumble {
+            intensity,
+            warnings,
+            pulse: None,
+            tick: 0,
+            danger: false,
+            on: false,
+        }
+    }
+
+    pub fn pulse(&mut self, pulse: Pulse) {
+        let replace = match self.pulse {
+            Some(current) => pulse.strength >= current.strength,
+            None => true,
+        };
+        if replace {
+            self.pulse = Some(pulse);
+            self.tick = 0;
+        }
+    }
+
+    pub fn events(&mut self, events: &[Event]) {
+        for event in events.iter() {
+            match event {
+                Event::Eat => self.pulse(EAT),
+                Event::Death => self.pulse(DEATH),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn danger(&mut self, danger: bool) {
+        if danger && !self.danger && self.warnings {
+            self.pulse(WARNING);
+        }
+        self.danger = danger;
+    }
+
+    // stops straight away, for pausing
+    pub fn stop<M: Motor>(&mut self, motor: &M) {
+        self.pulse = None;
+        self.set(motor, false);
+    }
+
+    // call once a frame, the motor is only written to when it changes
+    pub fn tick<M: Motor>(&mut self, motor: &M) {
+        let on = match self.pulse {
+            Some(pulse) if self.tick < pulse.ticks => {
+                let strength = (pulse.strength * self.intensity).clamp(0.0, 1.0);
+                let on_ticks = (strength * PERIOD as f32).round() as u32;
+                self.tick % PERIOD < on_ticks
+            }
+            _ => {
+                self.pulse = None;
+                false
+            }
+        };
+        self.tick += 1;
+        self.set(motor, on);
+    }
+
+    fn set<M: Motor>(&mut self, motor: &M, on: bool) {
+        if on != self.on {
+            motor.set_rumble(on);
+            self.on = on;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    // remembers every write so tests can check the motor is only touched when it changes
+    struct FakeMotor {
+        writes: RefCell<Vec<bool>>,
+    }
+
+    impl FakeMotor {
+        fn new() -> FakeMotor {
+            FakeMotor {
+                writes: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn on(&self) -> bool {
+            self.writes.borrow().last().cloned().unwrap_or(false)
+        }
+    }
+
+    impl Motor for FakeMotor {
+        fn set_rumble(&self, on: bool) {
+            self.writes.borrow_mut().push(on);
+        }
+    }
+
+    // whether the motor is on after each of the next ticks
+    fn run(rumble: &mut Rumble, motor: &FakeMotor, ticks: usize) -> Vec<bool> {
+        (0..ticks).map(|_| {
+            rumble.tick(motor);
+            motor.on()
+        }).collect()
+    }
+
+    #[test]
+    fn duty_cycle() {
+        let (t, f) = (true, false);
+
+        let motor = FakeMotor::new();
+        let mut rumble = Rumble::new(1.0, true);
+        rumble.pulse(Pulse { strength: 0.5, ticks: 8 });
+        assert!(run(&mut rumble, &motor, 10) == [t, t, f, f, t, t, f, f, f, f]);
+        assert!(*motor.writes.borrow() == [t, f, t, f]);
+
+        // a quarter is one tick in every four, full strength stays on with a single write
+        let motor = FakeMotor::new();
+        rumble.pulse(Pulse { strength: 0.25, ticks: 8 });
+        assert!(run(&mut rumble, &motor, 8) == [t, f, f, f, t, f, f, f]);
+        let motor = FakeMotor::new();
+        rumble.pulse(Pulse { strength: 1.0, ticks: 6 });
+        assert!(run(&mut rumble, &motor, 8) == [t, t, t, t, t, t, f, f]);
+        assert!(*motor.writes.borrow() == [t, f]);
+
+        // intensity scales it, 0 never touches the motor
+        let motor = FakeMotor::new();
+        rumble.intensity = 0.5;
+        rumble.pulse(DEATH);
+        assert!(run(&mut rumble, &motor, 4) == [t, t, f, f]);
+        let motor = FakeMotor::new();
+        rumble.intensity = 0.0;
+        rumble.pulse(DEATH);
+        run(&mut rumble, &motor, 40);
+        assert!(motor.writes.borrow().is_empty());
+    }
+
+    #[test]
+    fn priority() {
+        let motor = FakeMotor::new();
+        let mut rumble = Rumble::new(1.0, true);
+
+        // weaker pulses are dropped while a stronger one is going
+        rumble.pulse(DEATH);
+        run(&mut rumble, &motor, 10);
+        rumble.pulse(EAT);
+        assert!(run(&mut rumble, &motor, 20).iter().all(|&on| on));
+        assert!(run(&mut rumble, &motor, 1) == [false]);
+
+        // at least as strong replaces it and starts the new one from the beginning
+        rumble.pulse(EAT);
+        run(&mut rumble, &motor, 6);
+        rumble.pulse(EAT);
+        assert!(run(&mut rumble, &motor, 8).iter().filter(|&&on| on).count() == 4);
+        assert!(run(&mut rumble, &motor, 4).iter().all(|&on| !on));
+
+        // once a pulse is over anything can start
+        rumble.pulse(WARNING);
+        assert!(run(&mut rumble, &motor, 1) == [true]);
+    }
+
+    #[test]
+    fn warnings_and_stop() {
+        let motor = FakeMotor::new();
+        let mut rumble = Rumble::new(1.0, true);
+
+        // only when danger starts, not every frame it's there
+        rumble.danger(true);
+        run(&mut rumble, &motor, WARNING.ticks as usize);
+        rumble.danger(true);
+        assert!(run(&mut rumble, &motor, 4).iter().all(|&on| !on));
+
+        rumble.warnings = false;
+        rumble.danger(false);
+        rumble.danger(true);
+        assert!(run(&mut rumble, &motor, 4).iter().all(|&on| !on));
+
+        // pausing switches it off straight away and the pulse doesn't come back
+        rumble.pulse(DEATH);
+        run(&mut rumble, &motor, 1);
+        rumble.stop(&motor);
+        assert!(!motor.on());
+        assert!(run(&mut rumble, &motor, 4).iter().all(|&on| !on));
+    }
+}
@@ -0,0 +1,350 @@
+use dbsdk_rs::{io::IOError, math::Vector3};
+
+use card::Storage;
+use snake::Game;
+
+/*
+game snapshots, see Game::snapshot for what goes in one
+    suspending writes a snapshot to the memory card so a game can be picked up after the console's been off
+        it's only good once, resuming or the game ending discards it so an old game doesn't come back
+    quick saves stay in memory, for practicing the same spot over and over
+*/
+pub static FILE_NAME: &str = "snake_save";
+static MAGIC: &[u8; 4] = b"SNKG";
+// bump whenever anything in a snapshot changes, older snapshots are ignored rather than misread
+static VERSION: u8 = 6;
+
+// allocated the first time a snapshot is written, enough for the biggest of settings::ARENAS
+static BLOCKS: i32 = 40;
+
+// there's no deleting files, so a discarded snapshot is overwritten with the magic and a version nothing reads
+static DISCARDED: &[u8; 5] = b"SNKG\0";
+
+// numbers are little endian, floats are written as their bits so they come back exactly
+pub struct Writer {
+    pub bytes: Vec<u8>,
+}
+
+impl Default for Writer {
+    fn default() -> Writer {
+        Writer::new()
+    }
+}
+
+impl Writer {
+    pub fn new() -> Writer {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        Writer {
+            bytes,
+        }
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.bytes.push(value as u8);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn i32(&mut self, value: i32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn f32(&mut self, value: f32) {
+        self.u32(value.to_bits());
+    }
+
+    pub fn vec3(&mut self, value: Vector3) {
+        self.f32(value.x);
+        self.f32(value.y);
+        self.f32(value.z);
+    }
+
+    // length first, up to 255 bytes
+    pub fn str(&mut self, value: &str) {
+        let bytes = &value.as_bytes()[..value.len().min(255)];
+        self.u8(bytes.len() as u8);
+        self.bytes.extend_from_slice(bytes);
+    }
+}
+
+// every read is None once the bytes run out
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    // None if the bytes aren't a snapshot from this version
+    pub fn new(bytes: &'a [u8]) -> Option<Reader<'a>> {
+        if bytes.len() < 5 || &bytes[..4] != MAGIC || bytes[4] != VERSION {
+            return None
+        }
+        Some(Reader {
+            bytes,
+            position: 5,
+        })
+    }
+
+    fn take(&mut self, count: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes.get(self.position..self.position + count)?;
+        self.position += count;
+        Some(bytes)
+    }
+
+    pub fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    pub fn bool(&mut self) -> Option<bool> {
+        Some(self.u8()? == 1)
+    }
+
+    pub fn u16(&mut self) -> Option<u16> {
+        let bytes = self.take(2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        let bytes = self.take(4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn i32(&mut self) -> Option<i32> {
+        let bytes = self.take(4)?;
+        Some(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_bits(self.u32()?))
+    }
+
+    pub fn vec3(&mut self) -> Option<Vector3> {
+        Some(Vector3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    pub fn str(&mut self) -> Option<String> {
+        let length = self.u8()? as usize;
+        Some(String::from_utf8_lossy(self.take(length)?).into_owned())
+    }
+}
+
+pub struct Saves {
+    quick: Option<Vec<u8>>,
+}
+
+impl Default for Saves {
+    fn default() -> Saves {
+        Saves::new()
+    }
+}
+
+impl Saves {
+    pub fn new() -> Saves {
+        Saves {
+            quick: None,
+        }
+    }
+
+    pub fn has_quick(&self) -> bool {
+        self.quick.is_some()
+    }
+
+    pub fn quick_save(&mut self, game: &Game) {
+        self.quick = Some(game.snapshot());
+    }
+
+    // false if there's nothing saved yet
+    pub fn quick_load(&self, game: &mut Game) -> bool {
+        match &self.quick {
+            Some(bytes) => game.restore(bytes).is_some(),
+            None => false,
+        }
+    }
+}
+
+pub fn suspend<S: Storage>(storage: &mut S, game: &Game) -> Result<(), IOError> {
+    storage.write(FILE_NAME, &game.snapshot(), BLOCKS)
+}
+
+// false if there's no card, no snapshot on it, or it's from another version, a resumed snapshot is discarded
+pub fn resume<S: Storage>(storage: &mut S, game: &mut Game) -> bool {
+    let resumed = storage.read(FILE_NAME).is_some_and(|bytes| game.restore(&bytes).is_some());
+    if resumed {
+        let _ = discard(storage);
+    }
+    resumed
+}
+
+// only writes if there's a snapshot to get rid of, so a card that never had one isn't given the file
+pub fn discard<S: Storage>(storage: &mut S) -> Result<(), IOError> {
+    match storage.read(FILE_NAME) {
+        Some(bytes) if Reader::new(&bytes).is_some() => storage.write(FILE_NAME, DISCARDED, BLOCKS),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+    use snake::{Direction, Location, TickResult};
+    use std::collections::HashMap;
+
+    // writes go over the start of what's there like the card, nothing is ever truncated
+    struct FakeCard {
+        files: HashMap<String, Vec<u8>>,
+        writes: usize,
+    }
+
+    impl FakeCard {
+        fn new() -> FakeCard {
+            FakeCard {
+                files: HashMap::new(),
+                writes: 0,
+            }
+        }
+    }
+
+    impl Storage for FakeCard {
+        fn read(&self, name: &str) -> Option<Vec<u8>> {
+            self.files.get(name).cloned()
+        }
+
+        fn write(&mut self, name: &str, data: &[u8], _blocks: i32) -> Result<(), IOError> {
+            let file = self.files.entry(name.to_owned()).or_default();
+            if file.len() < data.len() {
+                file.resize(data.len(), 0);
+            }
+            file[..data.len()].copy_from_slice(data);
+            self.writes += 1;
+            Ok(())
+        }
+    }
+
+    fn game(seeds: [u8; 2]) -> Game {
+        Game::with_rng(16, 12, 4, 6, 0, Rng::from_seeds(seeds))
+    }
+
+    // heads for the food, so the snake grows and the board has something on it
+    fn steer(game: &mut Game) {
+        let food = (0..game.width).flat_map(|x| (0..game.height).map(move |y| (x, y)))
+            .find(|&(x, y)| matches!(game.at(x, y), Location::Food));
+        if let Some((x, y)) = food {
+            let direction = if x > game.head[0] {
+                Direction::Right
+            } else if x < game.head[0] {
+                Direction::Left
+            } else if y > game.head[1] {
+                Direction::Up
+            } else {
+                Direction::Down
+            };
+            game.set_direction(direction);
+        }
+    }
+
+    fn same_board(a: &Game, b: &Game) -> bool {
+        a.head == b.head && (0..a.width).all(|x| (0..a.height).all(|y| a.at(x, y) == b.at(x, y)))
+    }
+
+    #[test]
+    fn restored_game_plays_the_same() {
+        let mut original = game([12, 34]);
+        for _ in 0..60 {
+            steer(&mut original);
+            original.tick();
+        }
+        assert!(original.size > 1);
+
+        let mut restored = game([56, 78]);
+        assert!(restored.restore(&original.snapshot()).is_some());
+        assert!(same_board(&original, &restored));
+
+        for _ in 0..200 {
+            steer(&mut original);
+            steer(&mut restored);
+            let ended = !matches!(original.tick(), TickResult::Continue);
+            restored.tick();
+            assert!(same_board(&original, &restored));
+            assert!(original.size == restored.size);
+            if ended {
+                break
+            }
+        }
+    }
+
+    #[test]
+    fn bad_snapshots_leave_the_game_alone() {
+        let mut saved = game([12, 34]);
+        for _ in 0..30 {
+            steer(&mut saved);
+            saved.tick();
+        }
+        let bytes = saved.snapshot();
+
+        let mut game = game([56, 78]);
+        game.tick();
+        let before = game.snapshot();
+
+        for length in 0..bytes.len() {
+            assert!(game.restore(&bytes[..length]).is_none(), "restored {} of {} bytes", length, bytes.len());
+        }
+        for version in [0, VERSION - 1, VERSION + 1].iter() {
+            let mut other = bytes.clone();
+            other[4] = *version;
+            assert!(game.restore(&other).is_none());
+        }
+        let mut other = bytes.clone();
+        other[0] = b'X';
+        assert!(game.restore(&other).is_none());
+
+        assert!(game.snapshot() == before);
+    }
+
+    #[test]
+    fn suspended_games_only_resume_once() {
+        let mut card = FakeCard::new();
+        let mut played = game([12, 34]);
+        for _ in 0..30 {
+            steer(&mut played);
+            played.tick();
+        }
+
+        // nothing there to get rid of, so the card isn't touched
+        assert!(discard(&mut card).is_ok());
+        assert!(card.writes == 0 && card.files.is_empty());
+
+        assert!(suspend(&mut card, &played).is_ok());
+        let mut resumed = game([56, 78]);
+        assert!(resume(&mut card, &mut resumed));
+        assert!(same_board(&played, &resumed));
+
+        // the snapshot went with the resume, pausing again writes a new one
+        let before = resumed.snapshot();
+        assert!(!resume(&mut card, &mut resumed));
+        assert!(suspend(&mut card, &resumed).is_ok());
+        assert!(resume(&mut card, &mut game([1, 2])));
+
+        // a reset or a lose discards it without resuming
+        assert!(suspend(&mut card, &resumed).is_ok());
+        assert!(discard(&mut card).is_ok());
+        let writes = card.writes;
+        assert!(discard(&mut card).is_ok());
+        assert!(card.writes == writes);
+        let mut fresh = game([1, 2]);
+        assert!(!resume(&mut card, &mut fresh));
+        assert!(fresh.snapshot() == game([1, 2]).snapshot());
+        assert!(resumed.snapshot() == before);
+    }
+}
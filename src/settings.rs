@@ -0,0 +1,314 @@
+use dbsdk_rs::io::IOError;
+
+use card;
+use draw::CameraMode;
+use input::{Action, Binding, Bindings, ControlScheme, ACTIONS, BINDING_SLOTS};
+use stick::DeadzoneShape;
+use theme::{Theme, THEMES};
+use skin::{Skin, SKINS};
+use dressing::FloorPattern;
+
+// one per gamepad slot
+pub const PLAYERS: usize = 4;
+
+// board sizes to pick from, width by height, the first is the board the game always had
+pub static ARENAS: [[u16; 2]; 5] = [[12, 12], [16, 12], [20, 15], [24, 12], [12, 18]];
+
+/*
+player adjustable options
+    volumes are 0 to 1, the effective volume of a channel is master * channel
+    deadzone is how far a stick has to be pushed before it counts, 0 to 1
+    control schemes are per player, indexed by gamepad slot
+    rumble is the strength of controller feedback, 0 to 1, rumble warnings pulse when the next move would be fatal
+    arena is an index into ARENAS and theme is an index into theme::THEMES
+    skin is an index into skin::SKINS, best_score is kept here too so skins stay unlocked
+    floatiness scales how much everything drifts around, 0 to 1
+    floor_pattern is drawn over the floor tiles
+*/
+#[derive(Clone, Copy)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    pub deadzone: f32,
+    pub stick_shape: DeadzoneShape,
+    pub bindings: Bindings,
+    pub control_schemes: [ControlScheme; PLAYERS],
+    pub camera: CameraMode,
+    pub rumble: f32,
+    pub rumble_warnings: bool,
+    pub arena: usize,
+    pub theme: usize,
+    pub skin: usize,
+    pub best_score: u32,
+    pub floatiness: f32,
+    pub floor_pattern: FloorPattern,
+}
+
+// name on the memory card, and the first bytes of the file so we don't load something else by mistake
+pub static FILE_NAME: &str = "snake_settings";
+static MAGIC: &[u8; 4] = b"SNKS";
+static VERSION: u8 = 9;
+// bytes after the control schemes in each version, from 1 up
+static TRAILING: [usize; 9] = [0, 1, 2, 4, 5, 6, 9, 10, 11];
+// version 1 saved bindings for these, the rotate actions were added in the middle after it
+static V1_ACTIONS: [Action; 7] = [
+    Action::TurnUp, Action::TurnDown, Action::TurnLeft, Action::TurnRight,
+    Action::Pause, Action::Confirm, Action::Back,
+];
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings::new()
+    }
+}
+
+impl Settings {
+    pub const fn new() -> Settings {
+        Settings {
+            master_volume: 0.8,
+            sfx_volume: 1.0,
+            music_volume: 0.6,
+            deadzone: 0.2,
+            stick_shape: DeadzoneShape::Radial,
+            bindings: Bindings::classic(),
+            control_schemes: [ControlScheme::Absolute; PLAYERS],
+            camera: CameraMode::Fixed,
+            rumble: 0.6,
+            rumble_warnings: true,
+            arena: 0,
+            theme: 0,
+            skin: 0,
+            best_score: 0,
+            floatiness: 1.0,
+            floor_pattern: FloorPattern::Checkerboard,
+        }
+    }
+
+    pub fn sfx(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+
+    pub fn music(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    pub fn theme(&self) -> &'static Theme {
+        THEMES[self.theme.min(THEMES.len() - 1)]
+    }
+
+    // the chosen skin, or the first one if it's somehow been picked without being unlocked
+    pub fn skin(&self) -> &'static Skin {
+        match SKINS.get(self.skin) {
+            Some(skin) if skin.unlocked(self.best_score) => skin,
+            _ => &SKINS[0],
+        }
+    }
+
+    // true if it's a new best
+    pub fn record_score(&mut self, score: u32) -> bool {
+        if score <= self.best_score {
+            return false
+        }
+        self.best_score = score;
+        true
+    }
+
+    // width and height
+    pub fn arena_size(&self) -> [u16; 2] {
+        ARENAS[self.arena.min(ARENAS.len() - 1)]
+    }
+
+    /*
+    magic, version, then one byte each for the volumes and deadzone as percentages, then every binding slot,
+    then a byte for each player's control scheme, one for the camera and one for the deadzone shape,
+    then rumble as a percentage, whether warnings are on, the arena, the theme, the skin, the best score as two bytes, floatiness as a percentage and the floor pattern
+    see Binding::encode for the bindings
+    */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        for value in [self.master_volume, self.sfx_volume, self.music_volume, self.deadzone].iter() {
+            bytes.push((value * 100.0).round() as u8);
+        }
+        for slots in self.bindings.slots.iter() {
+            for binding in slots.iter() {
+                bytes.push(Binding::encode(*binding));
+            }
+        }
+        for scheme in self.control_schemes.iter() {
+            bytes.push(*scheme as u8);
+        }
+        bytes.push(self.camera as u8);
+        bytes.push(self.stick_shape as u8);
+        bytes.push((self.rumble * 100.0).round() as u8);
+        bytes.push(self.rumble_warnings as u8);
+        bytes.push(self.arena as u8);
+        bytes.push(self.theme as u8);
+        bytes.push(self.skin as u8);
+        bytes.extend_from_slice(&(self.best_score.min(u16::MAX as u32) as u16).to_le_bytes());
+        bytes.push((self.floatiness * 100.0).round() as u8);
+        bytes.push(self.floor_pattern as u8);
+        bytes
+    }
+
+    /*
+    None if the bytes aren't settings, or are from a newer version
+    everything after the bindings has been added on the end a version at a time, anything a file is too old to have keeps its default
+    */
+    pub fn from_bytes(bytes: &[u8]) -> Option<Settings> {
+        if bytes.len() < 5 || &bytes[..4] != MAGIC || bytes[4] == 0 || bytes[4] > VERSION {
+            return None
+        }
+        let version = bytes[4];
+        let actions: &[Action] = if version == 1 { &V1_ACTIONS } else { &ACTIONS };
+        let schemes = MAGIC.len() + 1 + 4 + actions.len() * BINDING_SLOTS;
+        // camera, deadzone shape, rumble, rumble warnings, arena, theme, skin, best score, floatiness, floor pattern
+        let rest = if version == 1 { schemes } else { schemes + PLAYERS };
+        let length = rest + TRAILING[version as usize - 1];
+        if bytes.len() < length {
+            return None
+        }
+
+        let byte = |i: usize| if i < length { Some(bytes[i]) } else { None };
+        let percent = |value: u8| (value as f32 / 100.0).min(1.0);
+        let mut settings = Settings::new();
+        settings.master_volume = percent(bytes[5]);
+        settings.sfx_volume = percent(bytes[6]);
+        settings.music_volume = percent(bytes[7]);
+        settings.deadzone = percent(bytes[8]);
+        for (i, action) in actions.iter().enumerate() {
+            for slot in 0..BINDING_SLOTS {
+                settings.bindings.slots[*action as usize][slot] = Binding::decode(bytes[9 + i * BINDING_SLOTS + slot]);
+            }
+        }
+        if version > 1 {
+            for (player, scheme) in settings.control_schemes.iter_mut().enumerate() {
+                *scheme = if bytes[schemes + player] == 1 { ControlScheme::Relative } else { ControlScheme::Absolute };
+            }
+        }
+
+        if let Some(camera) = byte(rest) {
+            settings.camera = if camera == 1 { CameraMode::Chase } else { CameraMode::Fixed };
+        }
+        if let Some(shape) = byte(rest + 1) {
+            settings.stick_shape = if shape == 1 { DeadzoneShape::Axial } else { DeadzoneShape::Radial };
+        }
+        if let Some(rumble) = byte(rest + 2) {
+            settings.rumble = percent(rumble);
+        }
+        if let Some(warnings) = byte(rest + 3) {
+            settings.rumble_warnings = warnings == 1;
+        }
+        if let Some(arena) = byte(rest + 4) {
+            settings.arena = (arena as usize).min(ARENAS.len() - 1);
+        }
+        if let Some(theme) = byte(rest + 5) {
+            settings.theme = (theme as usize).min(THEMES.len() - 1);
+        }
+        if let Some(skin) = byte(rest + 6) {
+            settings.skin = (skin as usize).min(SKINS.len() - 1);
+        }
+        if let (Some(low), Some(high)) = (byte(rest + 7), byte(rest + 8)) {
+            settings.best_score = u16::from_le_bytes([low, high]) as u32;
+        }
+        if let Some(floatiness) = byte(rest + 9) {
+            settings.floatiness = percent(floatiness);
+        }
+        if let Some(pattern) = byte(rest + 10) {
+            settings.floor_pattern = FloorPattern::from_u8(pattern);
+        }
+        Some(settings)
+    }
+
+    // whatever's on the memory card, or the defaults
+    pub fn load() -> Settings {
+        card::read(FILE_NAME).and_then(|bytes| Settings::from_bytes(&bytes)).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), IOError> {
+        card::write(FILE_NAME, &self.to_bytes(), 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dbsdk_rs::gamepad::GamepadButton;
+
+    fn changed() -> Settings {
+        let mut settings = Settings::new();
+        settings.master_volume = 0.5;
+        settings.bindings.set(Action::Pause, 1, Binding::Button(GamepadButton::Select));
+        settings.control_schemes[2] = ControlScheme::Relative;
+        settings.camera = CameraMode::Chase;
+        settings.rumble_warnings = false;
+        settings.arena = 2;
+        settings.best_score = 300;
+        settings.floatiness = 0.25;
+        settings.floor_pattern = FloorPattern::Gradient;
+        settings
+    }
+
+    // what an older version would have written, every version from 2 on is this version with the end cut off
+    fn older(version: u8) -> Vec<u8> {
+        let bytes = changed().to_bytes();
+        let length = bytes.len() - TRAILING[VERSION as usize - 1] + TRAILING[version as usize - 1];
+        let mut bytes = bytes[..length].to_vec();
+        bytes[4] = version;
+        bytes
+    }
+
+    #[test]
+    fn round_trip() {
+        let settings = Settings::from_bytes(&changed().to_bytes()).unwrap();
+        assert!(settings.master_volume == 0.5 && settings.camera == CameraMode::Chase && !settings.rumble_warnings);
+        assert!(settings.bindings.get(Action::Pause)[1] == Some(Binding::Button(GamepadButton::Select)));
+        assert!(settings.control_schemes[2] == ControlScheme::Relative);
+        assert!(settings.arena == 2 && settings.best_score == 300 && settings.floatiness == 0.25);
+        assert!(settings.floor_pattern == FloorPattern::Gradient);
+    }
+
+    #[test]
+    fn older_versions_fill_in_defaults() {
+        for version in 2..VERSION {
+            let settings = Settings::from_bytes(&older(version)).unwrap();
+            assert!(settings.master_volume == 0.5);
+            assert!(settings.control_schemes[2] == ControlScheme::Relative);
+            assert!(settings.camera == CameraMode::Chase);
+            assert!(settings.rumble_warnings == (version < 4));
+            assert!(settings.arena == if version < 5 { 0 } else { 2 });
+            assert!(settings.best_score == if version < 7 { 0 } else { 300 });
+            assert!(settings.floatiness == if version < 8 { 1.0 } else { 0.25 });
+            assert!(settings.floor_pattern == FloorPattern::Checkerboard);
+        }
+    }
+
+    #[test]
+    fn version_1_bindings() {
+        let mut bytes = changed().to_bytes()[..9].to_vec();
+        bytes[4] = 1;
+        for action in V1_ACTIONS.iter() {
+            for slot in 0..BINDING_SLOTS {
+                bytes.push(Binding::encode(changed().bindings.get(*action)[slot]));
+            }
+        }
+        let settings = Settings::from_bytes(&bytes).unwrap();
+        assert!(settings.master_volume == 0.5);
+        assert!(settings.bindings.get(Action::Pause)[1] == Some(Binding::Button(GamepadButton::Select)));
+        assert!(settings.bindings.get(Action::RotateLeft) == Bindings::classic().get(Action::RotateLeft));
+        assert!(settings.control_schemes[2] == ControlScheme::Absolute);
+        assert!(settings.camera == CameraMode::Fixed);
+    }
+
+    #[test]
+    fn rejects_newer_and_cut_short() {
+        let mut bytes = changed().to_bytes();
+        assert!(Settings::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        assert!(Settings::from_bytes(&older(3)[..older(3).len() - 1]).is_none());
+        bytes[4] = VERSION + 1;
+        assert!(Settings::from_bytes(&bytes).is_none());
+        bytes[4] = 0;
+        assert!(Settings::from_bytes(&bytes).is_none());
+    }
+}
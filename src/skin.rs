@@ -0,0 +1,78 @@
+use dbsdk_rs::math::Vector4;
+
+use crate::animation::track::Lerp;
+use crate::theme::Theme;
+use crate::util::vec4;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BodyPattern {
+    Gradient, // from the first color behind the head to the second at the tail
+    Stripes,  // bands of the two colors, painted on so they move with the body
+    Rainbow,  // every hue down the body, cycling over time
+}
+
+// segments in each stripe
+static STRIPE_LENGTH: u32 = 2;
+// how far round the hues go from one segment to the next and each vsync tick
+static RAINBOW_STEP: f32 = 0.07;
+static RAINBOW_SPEED: f32 = 0.004;
+
+/*
+how the body is colored
+    colors of None use the theme's body colors
+    a skin can only be picked once the best score has reached unlock_score
+*/
+#[derive(Clone, Copy)]
+pub struct Skin {
+    pub name: &'static str,
+    pub pattern: BodyPattern,
+    pub colors: Option<[Vector4; 2]>,
+    pub unlock_score: u32,
+}
+
+pub static SKINS: [Skin; 6] = [
+    Skin { name: "PLAIN", pattern: BodyPattern::Gradient, colors: None, unlock_score: 0 },
+    Skin { name: "STRIPED", pattern: BodyPattern::Stripes, colors: None, unlock_score: 10 },
+    Skin { name: "CORAL", pattern: BodyPattern::Stripes, colors: Some([Vector4::new(0.9, 0.1, 0.1, 1.0), Vector4::new(1.0, 0.85, 0.2, 1.0)]), unlock_score: 20 },
+    Skin { name: "EMBER", pattern: BodyPattern::Gradient, colors: Some([Vector4::new(1.0, 0.8, 0.1, 1.0), Vector4::new(0.6, 0.05, 0.0, 1.0)]), unlock_score: 30 },
+    Skin { name: "RAINBOW", pattern: BodyPattern::Rainbow, colors: None, unlock_score: 45 },
+    Skin { name: "GOLD", pattern: BodyPattern::Gradient, colors: Some([Vector4::new(1.0, 0.9, 0.4, 1.0), Vector4::new(0.7, 0.5, 0.1, 1.0)]), unlock_score: 60 },
+];
+
+impl Skin {
+    pub fn unlocked(&self, best_score: u32) -> bool {
+        best_score >= self.unlock_score
+    }
+
+    /*
+    color of one body segment
+        t is how far down the body it is, 0 right behind the head and 1 at the tail
+        segment counts up over the game for each new segment, so it stays the same for as long as the segment's there
+    */
+    pub fn color(&self, theme: &Theme, t: f32, segment: u32, tick: u32) -> Vector4 {
+        let colors = self.colors.unwrap_or(theme.body);
+        match self.pattern {
+            BodyPattern::Gradient => Vector4::lerp(colors[0], colors[1], t.clamp(0.0, 1.0)),
+            BodyPattern::Stripes => colors[((segment / STRIPE_LENGTH) % 2) as usize],
+            BodyPattern::Rainbow => {
+                let hue = (segment as f32 * RAINBOW_STEP + tick as f32 * RAINBOW_SPEED).fract();
+                hue_color(hue)
+            }
+        }
+    }
+}
+
+// fully saturated, hue is 0 to 1 starting at red
+fn hue_color(hue: f32) -> Vector4 {
+    let h = hue * 6.0;
+    let x = 1.0 - (h % 2.0 - 1.0).abs();
+    let (r, g, b) = match h as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    vec4(r, g, b, 1.0)
+}
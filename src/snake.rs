@@ -1,12 +1,22 @@
 use std::cmp::max;
-use dbsdk_rs::{vdp, math::Vector4};
+use std::collections::VecDeque;
+use dbsdk_rs::{vdp, math::Vector3};
 
 use rng;
-use draw;
-
-use crate::{util::{vec3, vec3_rand, vec3_from}, geometry::floaty::{StateFloaty, FloatyCamera}};
-
-#[derive(Clone, Copy)]
+use draw::{self, CameraMode, Segment};
+
+use crate::{util::{vec3, vec3_rand, vec3_from}, geometry::floaty::{StateFloaty, FloatyCamera, FloatyCameraOffsets}};
+use crate::animation::{presets, timeline::{Timeline, Target}};
+use crate::floor::{FloorEffects, FloorPalette, FloorState};
+use crate::dressing::{BoardDressing, FloorPattern};
+use crate::backdrop::{self, Backdrop};
+use crate::particles::{self, Emitter, ParticleSystem};
+use crate::render::Renderer;
+use crate::save::{Reader, Writer};
+use crate::theme::{self, FoodKind, Theme};
+use crate::skin::{Skin, SKINS};
+
+#[derive(Clone, Copy, PartialEq)]
 pub enum Direction {
     Up,
     Down,
@@ -14,18 +24,100 @@ pub enum Direction {
     Right,
 }
 
+// turning relative to the way the snake is going
+#[derive(Clone, Copy, PartialEq)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+impl Direction {
+    // up is +y and right is +x, so turning left from up goes left
+    pub fn turned(self, turn: Turn) -> Direction {
+        match (self, turn) {
+            (Direction::Up, Turn::Left) | (Direction::Down, Turn::Right) => Direction::Left,
+            (Direction::Up, Turn::Right) | (Direction::Down, Turn::Left) => Direction::Right,
+            (Direction::Left, Turn::Left) | (Direction::Right, Turn::Right) => Direction::Down,
+            (Direction::Left, Turn::Right) | (Direction::Right, Turn::Left) => Direction::Up,
+        }
+    }
+
+    // the other way round from `as u8`, anything unknown is right
+    pub fn from_u8(value: u8) -> Direction {
+        match value {
+            0 => Direction::Up,
+            1 => Direction::Down,
+            2 => Direction::Left,
+            _ => Direction::Right,
+        }
+    }
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    // one cell in this direction
+    pub fn vector(self) -> Vector3 {
+        match self {
+            Direction::Up => vec3(0.0, 1.0, 0.0),
+            Direction::Down => vec3(0.0, -1.0, 0.0),
+            Direction::Left => vec3(-1.0, 0.0, 0.0),
+            Direction::Right => vec3(1.0, 0.0, 0.0),
+        }
+    }
+
+    // degrees counterclockwise from up
+    pub fn angle(self) -> f32 {
+        match self {
+            Direction::Up => 0.0,
+            Direction::Left => 90.0,
+            Direction::Down => 180.0,
+            Direction::Right => 270.0,
+        }
+    }
+}
+
+static MAX_PARTICLES: usize = 256;
+
+// peak to peak drift in cells, floor tiles bob up and down and body segments sway a little as well
+static TILE_FLOAT: Vector3 = Vector3::new(0.0, 0.0, 0.12);
+static BODY_FLOAT: Vector3 = Vector3::new(0.06, 0.06, 0.2);
+
 pub struct Game {
-    pub width: u8,
-    pub height: u8,
-    table: Vec<i16>,
+    pub width: u16,
+    pub height: u16,
+    // what's in each cell, kept in step with body so lookups and moves don't have to search
+    grid: Vec<Cell>,
+    // every cell the body is in, newest first, so the front is right behind the head and the back is the tail
+    body: VecDeque<[u16; 2]>,
+    // counts every segment laid down, see Cell::Body
+    laid: u32,
 
     state_floaty: Vec<StateFloaty>,
     floaty_camera: FloatyCamera,
+    timeline: Timeline,
+    floor: FloorEffects,
+    dressing: BoardDressing,
+    backdrop: Backdrop,
+    particles: ParticleSystem,
+    theme: Theme,
+    pub skin: Skin,
+    // scales every floaty motion, the camera and backdrop included, 0 holds everything still
+    pub floatiness: f32,
     
-    pub size: u16,
+    pub camera: CameraMode,
+    // where the chase camera is looking and which way it's facing, eased towards the head every frame
+    chase_focus: Vector3,
+    chase_heading: f32,
+    pub size: u32,
     last_direction: Direction,
     direction: Direction,
-    pub head: [u8; 2],
+    pub head: [u16; 2],
 
     interval_frames: u32,
     interval_frame: u32,
@@ -34,6 +126,7 @@ pub struct Game {
 
     rng: rng::Rng,
     last_tick: TickResult<String, String>,
+    events: Vec<Event>,
 }
 
 #[derive(Clone)]
@@ -43,25 +136,49 @@ pub enum TickResult<W, L> {
     Continue
 }
 
-#[derive(Clone)]
+// things that happened since the last drain_events, for sound and anything else that reacts to the game
+#[derive(Clone, Copy, PartialEq)]
+pub enum Event {
+    Turn,
+    Eat,
+    Death,
+    Win,
+}
+
+// the u32 is which segment this was, counting from the start of the game, so the tail is always the smallest
+#[derive(Clone, Copy, PartialEq)]
+enum Cell {
+    Empty,
+    Food,
+    Body(u32),
+}
+
+#[derive(Clone, PartialEq)]
 pub enum Location {
-    Head(u16),
-    Body(u16),
+    Head(u32),
+    Body(u32),
     Food,
     Empty,
 }
 
 impl Game {
-    pub fn new(width: u8, height: u8, left: u8, top: u8, interval_frames: u32) -> Game {
-        let mut rng = rng::Rng::new();
+    pub fn new(width: u16, height: u16, left: u16, top: u16, interval_frames: u32) -> Game {
+        Game::with_rng(width, height, left, top, interval_frames, rng::Rng::new())
+    }
 
-        let mut table = Vec::with_capacity((width * height).into());
-        for _ in 0..width*height {
-            table.push(0);
-        }
+    pub fn with_rng(width: u16, height: u16, left: u16, top: u16, interval_frames: u32, rng: rng::Rng) -> Game {
+        let mut rng = rng;
+        // seeded from the game's seeds without drawing from it, so the same seed still gives the same game
+        let seeds = rng.current_seeds();
+        let particle_rng = rng::Rng::from_seeds([seeds[0] ^ 0x5a, seeds[1] ^ 0xa5]);
+
+        // all the cell counts are worked out in usize, a u16 multiply overflows long before a board gets too big to draw
+        let cells = (width as usize) * (height as usize);
+        let grid = vec![Cell::Empty; cells];
 
-        let mut state_floaty = Vec::with_capacity(((width+2)*(height+2)).into());
-        for _ in 0..(width+2)*(height+2) {
+        let floaty_cells = (width as usize + 2) * (height as usize + 2);
+        let mut state_floaty = Vec::with_capacity(floaty_cells);
+        for _ in 0..floaty_cells {
             state_floaty.push(StateFloaty::new(
                 vec3_rand(&mut rng, 90, 240),
                 vec3_rand(&mut rng, 0, 120),
@@ -72,16 +189,30 @@ impl Game {
         let mut game = Game {
             width,
             height,
-            table,
+            grid,
+            body: VecDeque::new(),
+            laid: 0,
 
             state_floaty,
             floaty_camera: FloatyCamera::new(&mut rng,
                 vec3(5.0, 5.0, 1.0),
-                vec3(0.15, 0.05, 0.1),
-                10 * 60, 25 * 60,
-                0, 15 * 60
+                vec3(17.0, 6.0, 11.0), // degrees
+                // vsync ticks, periods of 80 to 280 seconds
+                80 * 60, 200 * 60,
+                0, 120 * 60
             ),
+            timeline: Timeline::new(),
+            floor: FloorEffects::new(FloorPalette::classic(), cells, 40, 45),
+            dressing: BoardDressing::classic(),
+            backdrop: Backdrop::new(),
+            particles: ParticleSystem::new(MAX_PARTICLES, particle_rng),
+            theme: theme::CLASSIC,
+            skin: SKINS[0],
+            floatiness: 1.0,
             
+            camera: CameraMode::Fixed,
+            chase_focus: Vector3::zero(),
+            chase_heading: Direction::Right.angle(),
             size: 1,
             last_direction: Direction::Right,
             direction: Direction::Right,
@@ -94,9 +225,10 @@ impl Game {
 
             rng,
             last_tick: TickResult::Continue,
+            events: Vec::new(),
         };
         let _ = game.new_food(); // rng will be consistent if i call it here
-        return game
+        game
     }
 
     pub fn reset(&mut self) {
@@ -108,11 +240,11 @@ impl Game {
         self.frame = 0;
         // self.tick = 0;
         self.last_tick = TickResult::Continue;
-        self.table = Vec::with_capacity((self.width * self.height).into());
-        for _ in 0..self.width*self.height {
-            self.table.push(0);
-        }
+        self.grid = vec![Cell::Empty; self.cells()];
+        self.body.clear();
+        self.floor.reset();
         let _ = self.new_food();
+        self.timeline.play(Target::Board, presets::level_transition());
     }
 
     pub fn set_direction(&mut self, direction: Direction) {
@@ -132,71 +264,309 @@ impl Game {
         self.direction = direction;
     }
 
-    pub fn at(&self, x: u8, y: u8) -> Location {
+    // turns relative to the last move, so two turns before the next move don't double back
+    pub fn turn(&mut self, turn: Turn) {
+        self.direction = self.last_direction.turned(turn);
+    }
+
+    pub fn set_floor_pattern(&mut self, pattern: FloorPattern) {
+        self.dressing.pattern = pattern;
+    }
+
+    // also replaces the floor, dressing and backdrop palettes
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = *theme;
+        self.floor.set_palette(theme.floor);
+        self.dressing.palette = theme.dressing;
+        self.backdrop.palette = theme.backdrop;
+    }
+
+    // food eaten since the last reset
+    pub fn score(&self) -> u32 {
+        self.size - 1
+    }
+
+    // vsyncs between moves, minus one
+    pub fn interval_frames(&self) -> u32 {
+        self.interval_frames
+    }
+
+    // everything that happened since this was last called, oldest first
+    pub fn drain_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /*
+    everything that decides how the game plays from here, so a restored game ticks exactly the same
+        board size, how many segments have been laid, the body from the head back, food, head, size, directions, interval counters, tick, rng seeds, the last tick's result,
+        then every cell's StateFloaty, the FloatyCamera and where the chase camera is
+    animations, particles and floor trails are only looks and start fresh
+    */
+    pub fn snapshot(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer.u16(self.width);
+        writer.u16(self.height);
+        writer.u32(self.laid);
+        writer.u32(self.body.len() as u32);
+        for pos in self.body.iter() {
+            writer.u16(pos[0]);
+            writer.u16(pos[1]);
+        }
+        let food: Vec<usize> = (0..self.grid.len()).filter(|&i| self.grid[i] == Cell::Food).collect();
+        writer.u32(food.len() as u32);
+        for index in food {
+            writer.u32(index as u32);
+        }
+        writer.u16(self.head[0]);
+        writer.u16(self.head[1]);
+        writer.u32(self.size);
+        writer.u8(self.last_direction as u8);
+        writer.u8(self.direction as u8);
+
+        writer.u32(self.interval_frames);
+        writer.u32(self.interval_frame);
+        writer.u32(self.frame);
+        writer.u32(self.tick);
+        let seeds = self.rng.current_seeds();
+        writer.u8(seeds[0]);
+        writer.u8(seeds[1]);
+        match &self.last_tick {
+            TickResult::Continue => writer.u8(0),
+            TickResult::Win(msg) => {
+                writer.u8(1);
+                writer.str(msg);
+            }
+            TickResult::Lose(msg) => {
+                writer.u8(2);
+                writer.str(msg);
+            }
+        }
+
+        for state_floaty in self.state_floaty.iter() {
+            writer.vec3(state_floaty.ticks);
+            writer.vec3(state_floaty.tick_offset);
+            writer.vec3(state_floaty.magnitude);
+        }
+        for i in 0..6 {
+            writer.u32(self.floaty_camera.ticks[i]);
+            writer.u32(self.floaty_camera.tick_offset[i]);
+            writer.f32(self.floaty_camera.magnitude[i]);
+        }
+        writer.vec3(self.chase_focus);
+        writer.f32(self.chase_heading);
+
+        writer.bytes
+    }
+
+    // None if the bytes aren't a whole snapshot from this version, the game is left as it was
+    pub fn restore(&mut self, bytes: &[u8]) -> Option<()> {
+        let mut reader = Reader::new(bytes)?;
+        let width = reader.u16()?;
+        let height = reader.u16()?;
+        if width == 0 || height == 0 {
+            return None
+        }
+        let cells = (width as usize) * (height as usize);
+        // segments keep their numbers, skins and floaty drift are keyed by them
+        let mut grid = vec![Cell::Empty; cells];
+        let mut body = VecDeque::new();
+        let laid = reader.u32()?;
+        let segments = reader.u32()?;
+        if segments > laid {
+            return None
+        }
+        for i in 0..segments {
+            let pos = [reader.u16()?, reader.u16()?];
+            if pos[0] >= width || pos[1] >= height {
+                return None
+            }
+            grid[(pos[1] as usize) * (width as usize) + (pos[0] as usize)] = Cell::Body(laid - 1 - i);
+            body.push_back(pos);
+        }
+        for _ in 0..reader.u32()? {
+            let index = reader.u32()? as usize;
+            *grid.get_mut(index)? = Cell::Food;
+        }
+        let head = [reader.u16()?, reader.u16()?];
+        if head[0] >= width || head[1] >= height {
+            return None
+        }
+        let size = reader.u32()?;
+        let last_direction = Direction::from_u8(reader.u8()?);
+        let direction = Direction::from_u8(reader.u8()?);
+
+        let interval_frames = reader.u32()?;
+        let interval_frame = reader.u32()?;
+        let frame = reader.u32()?;
+        let tick = reader.u32()?;
+        let seeds = [reader.u8()?, reader.u8()?];
+        let last_tick = match reader.u8()? {
+            1 => TickResult::Win(reader.str()?),
+            2 => TickResult::Lose(reader.str()?),
+            _ => TickResult::Continue,
+        };
+
+        let floaty_cells = (width as usize + 2) * (height as usize + 2);
+        let mut state_floaty = Vec::with_capacity(floaty_cells);
+        for _ in 0..floaty_cells {
+            state_floaty.push(StateFloaty::new(reader.vec3()?, reader.vec3()?, reader.vec3()?));
+        }
+        let mut floaty_camera = self.floaty_camera;
+        for i in 0..6 {
+            floaty_camera.ticks[i] = reader.u32()?;
+            floaty_camera.tick_offset[i] = reader.u32()?;
+            floaty_camera.magnitude[i] = reader.f32()?;
+        }
+        let chase_focus = reader.vec3()?;
+        let chase_heading = reader.f32()?;
+
+        if cells != self.grid.len() {
+            self.floor.resize(cells);
+        } else {
+            self.floor.reset();
+        }
+        self.width = width;
+        self.height = height;
+        self.grid = grid;
+        self.body = body;
+        self.laid = laid;
+        self.head = head;
+        self.size = size;
+        self.last_direction = last_direction;
+        self.direction = direction;
+        self.interval_frames = interval_frames;
+        self.interval_frame = interval_frame;
+        self.frame = frame;
+        self.tick = tick;
+        self.rng = rng::Rng::from_seeds(seeds);
+        self.last_tick = last_tick;
+        self.state_floaty = state_floaty;
+        self.floaty_camera = floaty_camera;
+        self.chase_focus = chase_focus;
+        self.chase_heading = chase_heading;
+        self.timeline = Timeline::new();
+        self.particles.clear();
+        self.events.clear();
+        Some(())
+    }
+
+    pub fn cells(&self) -> usize {
+        (self.width as usize) * (self.height as usize)
+    }
+
+    // the board's width and height in board units, the longer side is 1
+    pub fn extent(&self) -> [f32; 2] {
+        let longest = max(self.width, self.height) as f32;
+        [self.width as f32 / longest, self.height as f32 / longest]
+    }
+
+    pub fn at(&self, x: u16, y: u16) -> Location {
         if x == self.head[0] && y == self.head[1] {
             return Location::Head(self.size)
         }
-        let val = self.get(x, y);
-        if val == -1 {
-            return Location::Food
+        match self.get(x, y) {
+            Cell::Food => Location::Food,
+            // how many moves until it's gone, 1 for the tail
+            Cell::Body(segment) => Location::Body(segment - self.tail_segment() + 1),
+            Cell::Empty => Location::Empty,
         }
-        if val > 0 {
-            return Location::Body(val as u16)
+    }
+
+    // from right behind the head back to the tail
+    pub fn body(&self) -> &VecDeque<[u16; 2]> {
+        &self.body
+    }
+
+    fn tail_segment(&self) -> u32 {
+        match self.body.back() {
+            Some(tail) => match self.get(tail[0], tail[1]) {
+                Cell::Body(segment) => segment,
+                _ => 0,
+            },
+            None => 0,
         }
-        Location::Empty
     }
 
-    fn get_state_floaty_index(&self, x: i8, y: i8) -> usize {
-        let x = (x + 1) as u8;
-        let y = (y + 1) as u8;
-        if x >= (self.width + 2) || y >= (self.height + 2) {
+    fn is_body(&self, pos: [u16; 2]) -> bool {
+        matches!(self.get(pos[0], pos[1]), Cell::Body(_))
+    }
+
+    // -1 to width/height, one cell of margin all the way round for the wrap predictions
+    fn get_state_floaty_index(&self, x: i32, y: i32) -> usize {
+        let x = x + 1;
+        let y = y + 1;
+        if x < 0 || y < 0 || x >= self.width as i32 + 2 || y >= self.height as i32 + 2 {
             return 0
         }
-        (y as usize) * ((self.width + 2) as usize) + (x as usize)
+        (y as usize) * (self.width as usize + 2) + (x as usize)
+    }
+    // the drift for anything sitting in a cell, evaluate it at self.tick
+    pub fn state_floaty(&self, x: i32, y: i32) -> StateFloaty {
+        self.state_floaty[self.get_state_floaty_index(x, y)].scaled(self.floatiness)
     }
-    pub fn tick_state_floaty(&mut self, x: i8, y: i8) -> StateFloaty {
-        let index = self.get_state_floaty_index(x, y);
-        self.state_floaty[index].tick();
-        self.state_floaty[index]
+
+    // keyed by segment number, so a segment keeps swaying the same way as it moves, the head is the segment it's about to lay down
+    fn segment_float(&self, segment: u32, size: f32) -> Vector3 {
+        StateFloaty::from_key(segment, BODY_FLOAT * self.floatiness).offset(size, self.tick)
     }
 
-    fn get_index(&self, x: u8, y: u8) -> usize {
+    fn get_index(&self, x: u16, y: u16) -> usize {
         if x >= self.width || y >= self.height {
             return 0
         }
         (y as usize) * (self.width as usize) + (x as usize)
     }
-    fn get(&self, x: u8, y: u8) -> i16 {
-        self.table[self.get_index(x, y)]
+    fn get(&self, x: u16, y: u16) -> Cell {
+        self.grid[self.get_index(x, y)]
     }
-    fn set(&mut self, x: u8, y: u8, value: i16) {
+    fn set(&mut self, x: u16, y: u16, value: Cell) {
         let index = self.get_index(x, y);
-        self.table[index] = value;
+        self.grid[index] = value;
     }
 
     fn new_food(&mut self) -> Result<(), &str> {
-        let pos = self.rng.random(self.width);
+        // boards that fit in a u8 take one step of the rng like they always have, so a seed still gives the same game on a square board
+        let pos = if self.width <= u8::MAX as u16 && self.height <= u8::MAX as u16 {
+            let num = self.rng.next_pair();
+            [num[0] as u16 % self.width, num[1] as u16 % self.height]
+        } else {
+            [self.rng.random_single(self.width), self.rng.random_single(self.height)]
+        };
 
+        let cells = self.cells();
         let index = self.get_index(pos[0], pos[1]);
         let mut off = 0;
 
+        // wraps back to the first cell so a crowded end of the board doesn't run off the grid
         loop {
-            if self.table[index+off] == 0 {
+            if self.grid[(index + off) % cells] == Cell::Empty {
                 break;
             }
-            if off >= (self.width * self.height).into() {
+            if off >= cells {
                 return Err("No space for food")
             }
             off+=1
         }
+        let index = (index + off) % cells;
+
+        self.grid[index] = Cell::Food;
 
-        self.table[index+off] = -1;
+        let x = (index % (self.width as usize)) as u16;
+        let y = (index / (self.width as usize)) as u16;
+        self.timeline.stop(Target::Cell(x, y));
+        self.timeline.play(Target::Cell(x, y), presets::food_spawn());
+        let dust = Emitter { color: [self.theme.food(FoodKind::Normal), self.theme.floor.empty], ..particles::food_spawn() };
+        self.particles.emit(&dust, cell_center(x, y, 0.1));
 
         Ok(())
     }
 
     pub fn tick(&mut self) -> TickResult<String, String> {
         self.tick += 1;
+        self.timeline.tick();
+        self.floor.tick();
+        self.particles.tick();
+        self.tick_chase();
 
         if !matches!(self.last_tick, TickResult::Continue) {
             return self.last_tick.clone()
@@ -210,147 +580,428 @@ impl Game {
         self.frame += 1;
 
         let result = self.tick_internal();
+        match result {
+            TickResult::Lose(_) => {
+                self.timeline.play(Target::Camera, presets::death_shake());
+                let pieces = Emitter { color: [self.theme.head, self.theme.body[1]], ..particles::death_explosion() };
+                self.particles.emit(&pieces, cell_center(self.head[0], self.head[1], 0.5));
+                self.events.push(Event::Death);
+            }
+            TickResult::Win(_) => self.events.push(Event::Win),
+            TickResult::Continue => {}
+        }
         self.last_tick = result.clone();
         result
     }
 
-    fn tick_internal(&mut self) -> TickResult<String, String> {
-        // set head
-        self.set(self.head[0], self.head[1], self.size as i16);
+    fn tick_chase(&mut self) {
+        let size = 1.0 / (max(self.width, self.height) as f32);
+        let focus = cell_center(self.head[0], self.head[1], 0.0) * size;
 
-        // move head
-        match self.direction {
+        // snap rather than sweep across the board when the head wraps around
+        if Vector3::distance(&focus, &self.chase_focus) > size * 2.0 {
+            self.chase_focus = focus;
+        } else {
+            self.chase_focus = self.chase_focus + (focus - self.chase_focus) * 0.15;
+        }
+
+        // the short way round
+        let mut turn = (self.last_direction.angle() - self.chase_heading) % 360.0;
+        if turn > 180.0 {
+            turn -= 360.0;
+        } else if turn < -180.0 {
+            turn += 360.0;
+        }
+        self.chase_heading = (self.chase_heading + turn * 0.12) % 360.0;
+    }
+
+    // one step from pos, wrapping around the edges
+    fn step(&self, pos: [u16; 2], direction: Direction) -> [u16; 2] {
+        let mut pos = pos;
+        match direction {
             Direction::Up => {
-                if self.head[1] == self.height - 1 {
-                    self.head[1] = 0;
+                if pos[1] == self.height - 1 {
+                    pos[1] = 0;
                 } else {
-                    self.head[1] += 1;
+                    pos[1] += 1;
                 }
             },
             Direction::Down => {
-                if self.head[1] == 0 {
-                    self.head[1] = self.height - 1;
+                if pos[1] == 0 {
+                    pos[1] = self.height - 1;
                 } else {
-                    self.head[1] -= 1;
+                    pos[1] -= 1;
                 }
             },
             Direction::Left => {
-                if self.head[0] == 0 {
-                    self.head[0] = self.width - 1;
+                if pos[0] == 0 {
+                    pos[0] = self.width - 1;
                 } else {
-                    self.head[0] -= 1;
+                    pos[0] -= 1;
                 }
             },
             Direction::Right => {
-                if self.head[0] == self.width - 1 {
-                    self.head[0] = 0;
+                if pos[0] == self.width - 1 {
+                    pos[0] = 0;
                 } else {
-                    self.head[0] += 1;
+                    pos[0] += 1;
                 }
             },
         }
+        pos
+    }
+
+    // which way to go from one cell to get to the one next to it, wrapping around the edges, None if they aren't next to each other
+    fn direction_between(&self, from: [u16; 2], to: [u16; 2]) -> Option<Direction> {
+        [Direction::Up, Direction::Down, Direction::Left, Direction::Right].iter()
+            .find(|&&direction| self.step(from, direction) == to)
+            .copied()
+    }
+
+    // where the head will be after the next move
+    pub fn next_head(&self) -> [u16; 2] {
+        self.step(self.head, self.direction)
+    }
+
+    /*
+    cells just outside the board that show where a snake cell on the edge comes back in
+        positions are one cell past the opposite edge, so -1 or width/height
+        the weight is the side of the prediction box facing the board
+    */
+    pub fn wrap_predictions(&self, x: u16, y: u16) -> Vec<([i32; 2], Vector3)> {
+        let mut predictions = Vec::new();
+        if x == 0 {
+            predictions.push(([self.width as i32, y as i32], vec3(0.0, 0.5, 0.0)));
+        }
+        if y == 0 {
+            predictions.push(([x as i32, self.height as i32], vec3(0.5, 0.0, 0.0)));
+        }
+        if x == self.width - 1 {
+            predictions.push(([-1, y as i32], vec3(1.0, 0.5, 0.0)));
+        }
+        if y == self.height - 1 {
+            predictions.push(([x as i32, -1], vec3(0.5, 1.0, 0.0)));
+        }
+        predictions
+    }
+
+    // whether moving in the current direction would end the game
+    pub fn danger_ahead(&self) -> bool {
+        let next = self.next_head();
+        self.is_body(next)
+    }
+
+    fn tick_internal(&mut self) -> TickResult<String, String> {
+        // the cell the head leaves becomes the newest body segment
+        self.set(self.head[0], self.head[1], Cell::Body(self.laid));
+        self.body.push_front(self.head);
+        self.laid += 1;
+
+        // move head
+        self.head = self.next_head();
+        if self.direction != self.last_direction {
+            self.events.push(Event::Turn);
+        }
         self.last_direction = self.direction;
 
-        // check if we hit ourselves
-        if self.get(self.head[0], self.head[1]) > 0 {
+        // check if we hit ourselves, the tail hasn't moved yet so running into it counts
+        if self.is_body(self.head) {
             return TickResult::Lose("Ouroboros".to_owned())
         }
 
         // check if we hit food
-        if self.get(self.head[0], self.head[1]) < 0 {
+        if self.get(self.head[0], self.head[1]) == Cell::Food {
             self.size += 1;
+            self.events.push(Event::Eat);
+            self.timeline.stop(Target::Cell(self.head[0], self.head[1]));
+            self.timeline.play(Target::Cell(self.head[0], self.head[1]), presets::food_eaten());
+            let chunks = Emitter { color: [self.theme.food(FoodKind::Normal), self.theme.food(FoodKind::Bonus)], ..particles::food_eaten() };
+            self.particles.emit(&chunks, cell_center(self.head[0], self.head[1], 0.5));
             if let Err(str) = self.new_food() {
                 if str == "No space for food" {
                     return TickResult::Win("Yummers".to_owned())
                 }
                 return TickResult::Lose("Garbage".to_owned())
             }
-        } else {
-            // decay snake
-            for i in 0..(self.width*self.height).into() {
-                if self.table[i] > 0 {
-                    self.table[i] -= 1;
-                }
-            }
+        } else if let Some(tail) = self.body.pop_back() {
+            // the tail follows on
+            self.set(tail[0], tail[1], Cell::Empty);
+            self.floor.leave(self.get_index(tail[0], tail[1]));
         }
 
         TickResult::Continue
     }
 
-    pub fn draw(&mut self) {
-        vdp::clear_color(vdp::Color32::new(0, 0, 0, 255));
-        vdp::clear_depth(1.0);
+    pub fn draw<R: Renderer>(&mut self, renderer: &mut R) {
+        renderer.clear(self.theme.background, 1.0);
+
+        let (mut tris, cam_offsets) = self.scene();
+
+        // drawn first and further away than anything on the board, so the board's depth test puts it in front
+        let mut backdrop_tris = self.backdrop.tris(self.tick, self.floatiness);
+        draw::transform_draw_tris(renderer, &mut backdrop_tris, &backdrop::camera_matrices(&cam_offsets));
+
+        let camera = match self.camera {
+            CameraMode::Fixed => draw::camera_matrices(cam_offsets, self.extent()).to_vec(),
+            CameraMode::Chase => draw::chase_camera_matrices(cam_offsets, self.chase_focus, self.chase_heading).to_vec(),
+        };
+        draw::transform_draw_tris(renderer, &mut tris, &camera)
+    }
 
+    // untransformed tris for the whole board and where the camera should be this frame
+    pub fn scene(&mut self) -> (Vec<vdp::Vertex>, FloatyCameraOffsets) {
         let mut tris = Vec::<vdp::Vertex>::new();
 
         let size = 1.0 / (max(self.width, self.height) as f32);
 
+        // the cell we're about to move into gets tinted as the move gets closer, more if it'd kill us
+        let next = self.next_head();
+        let progress = (self.interval_frame as f32) / ((self.interval_frames + 1) as f32);
+        let danger = progress * if self.is_body(next) { 1.0 } else { 0.35 };
+
         // loop through the game grid
         for x in 0..self.width {
             for y in 0..self.height {
-
-                match self.at(x, y) {
+                let location = self.at(x, y);
+
+                let floor_state = match location {
+                    Location::Food => FloorState::Food,
+                    Location::Empty => FloorState::Empty,
+                    _ => FloorState::Occupied,
+                };
+                let floor_danger = if x == next[0] && y == next[1] { danger } else { 0.0 };
+                let floor_color = self.floor.color(self.get_index(x, y), floor_state, floor_danger, self.tick)
+                    * self.dressing.tint(x, y, self.width, self.height);
+
+                // tiles don't move, so the cell is as good a key as any
+                let start = tris.len();
+                draw::floor_box(&mut tris, x as f32, y as f32, 0.0, size, floor_color);
+                let tile_float = StateFloaty::from_key(self.get_index(x, y) as u32, TILE_FLOAT * self.floatiness).offset(size, self.tick);
+                draw::float_joined(&mut tris[start..], x as f32, y as f32, size, tile_float, None, None);
+
+                match location {
                     Location::Head(_) => {
-                        draw::floor_box(&mut tris, x as f32, y as f32, 0.0, size, Vector4::new(0.15, 0.15, 0.15, 1.0));
-                        
-                        let scale = if self.size == 1 { 0.6 } else if self.size == 2 { 0.6 } else if self.size == 3 { 0.8 } else { 1.0 };
-                        draw::body_box(&mut tris, true, x as f32, y as f32, 0.0, size, scale);
+                        let scale = if self.size <= 2 { 0.6 } else if self.size == 3 { 0.8 } else { 1.0 };
+                        let back = self.body.front().and_then(|&pos| self.direction_between(self.head, pos));
+                        let start = tris.len();
+                        let segment = Segment { forward: self.direction, back, from_tail: self.body.len() as f32 };
+                        draw::head_segment(&mut tris, vec3(x as f32, y as f32, 0.0), size, segment, scale, &self.theme);
+                        let back_float = back.map(|direction| (direction, self.segment_float(self.laid.wrapping_sub(1), size)));
+                        draw::float_joined(&mut tris[start..], x as f32, y as f32, size, self.segment_float(self.laid, size), None, back_float);
+                        self.animate_cell(&mut tris[start..], x, y, size);
                         
-                        if x == 0 {
-                            let state_floaty = self.tick_state_floaty(self.width as i8, y as i8);
-                            draw::body_prediction_box(&mut tris, true, self.width as f32, y as f32, 0.0, size, vec3(0.0, 0.5, 0.0), state_floaty);
-                        }
-                        if y == 0 {
-                            let state_floaty = self.tick_state_floaty(x as i8, self.height as i8);
-                            draw::body_prediction_box(&mut tris, true, x as f32, self.height as f32, 0.0, size, vec3(0.5, 0.0, 0.0), state_floaty);
-                        }
-                        if x == self.width - 1 {
-                            let state_floaty = self.tick_state_floaty(-1, y as i8);
-                            draw::body_prediction_box(&mut tris, true, -1.0, y as f32, 0.0, size, vec3(1.0, 0.5, 0.0), state_floaty);
-                        }
-                        if y == self.height - 1 {
-                            let state_floaty = self.tick_state_floaty(x as i8, -1);
-                            draw::body_prediction_box(&mut tris, true, x as f32, -1.0, 0.0, size, vec3(0.5, 1.0, 0.0), state_floaty);
+                        for (pos, weight) in self.wrap_predictions(x, y) {
+                            let state_floaty = self.state_floaty(pos[0], pos[1]);
+                            draw::body_prediction_box(&mut tris, self.theme.head, vec3(pos[0] as f32, pos[1] as f32, 0.0), size, weight, state_floaty, self.tick);
                         }
                     }
                     Location::Body(val) => {
-                        draw::floor_box(&mut tris, x as f32, y as f32, 0.0, size, Vector4::new(0.15, 0.15, 0.15, 1.0));
+                        // val counts up from the tail, so the segment's place in the body is known without searching
+                        let index = self.body.len() - val as usize;
+                        let ahead = if index == 0 { self.head } else { self.body[index - 1] };
+                        let forward = self.direction_between([x, y], ahead).unwrap_or(self.last_direction);
+                        let back = self.body.get(index + 1).and_then(|&pos| self.direction_between([x, y], pos));
+                        let start = tris.len();
+                        let segment = self.tail_segment() + val - 1;
+                        let color = self.skin.color(&self.theme, index as f32 / (self.body.len() - 1).max(1) as f32, segment, self.tick);
+                        draw::body_segment(&mut tris, vec3(x as f32, y as f32, 0.0), size, Segment { forward, back, from_tail: (val - 1) as f32 }, color);
+                        let forward_float = Some((forward, self.segment_float(segment + 1, size)));
+                        let back_float = back.map(|direction| (direction, self.segment_float(segment.wrapping_sub(1), size)));
+                        draw::float_joined(&mut tris[start..], x as f32, y as f32, size, self.segment_float(segment, size), forward_float, back_float);
+                        self.animate_cell(&mut tris[start..], x, y, size);
                         
-                        let scale: f32 = if val == 1 { 0.4 } else if val == 2 { 0.65 } else if val == 3 { 0.9 } else { 1.0 };
-                        draw::body_box(&mut tris, false, x as f32, y as f32, 0.0, size, scale);
-                        
-                        if x == 0 {
-                            let state_floaty = self.tick_state_floaty(self.width as i8, y as i8);
-                            draw::body_prediction_box(&mut tris, false, self.width as f32, y as f32, 0.0, size, vec3(0.0, 0.5, 0.0), state_floaty);
-                        }
-                        if y == 0 {
-                            let state_floaty = self.tick_state_floaty(x as i8, self.height as i8);
-                            draw::body_prediction_box(&mut tris, false, x as f32, self.height as f32, 0.0, size, vec3(0.5, 0.0, 0.0), state_floaty);
-                        }
-                        if x == self.width - 1 {
-                            let state_floaty = self.tick_state_floaty(-1, y as i8);
-                            draw::body_prediction_box(&mut tris, false, -1.0, y as f32, 0.0, size, vec3(1.0, 0.5, 0.0), state_floaty);
-                        }
-                        if y == self.height - 1 {
-                            let state_floaty = self.tick_state_floaty(x as i8, -1);
-                            draw::body_prediction_box(&mut tris, false, x as f32, -1.0, 0.0, size, vec3(0.5, 1.0, 0.0), state_floaty);
+                        for (pos, weight) in self.wrap_predictions(x, y) {
+                            let state_floaty = self.state_floaty(pos[0], pos[1]);
+                            draw::body_prediction_box(&mut tris, color, vec3(pos[0] as f32, pos[1] as f32, 0.0), size, weight, state_floaty, self.tick);
                         }
                     }
                     Location::Food => {
-                        draw::floor_box(&mut tris, x as f32, y as f32, 0.0, size, Vector4::new(0.15, 0.15, 0.15, 1.0));
-                        
-                        let state_floaty = self.tick_state_floaty(x as i8, y as i8);
-                        draw::food_box(&mut tris, x as f32, y as f32, 0.0, size, state_floaty);
-                    }
-                    Location::Empty => {
-                        draw::floor_box(&mut tris, x as f32, y as f32, 0.0, size, Vector4::new(0.25, 0.25, 0.25, 1.0));
+                        let state_floaty = self.state_floaty(x as i32, y as i32);
+                        let start = tris.len();
+                        draw::food_box(&mut tris, vec3(x as f32, y as f32, 0.0), size, self.theme.food(FoodKind::Normal), state_floaty, self.tick);
+                        self.animate_cell(&mut tris[start..], x, y, size);
                     }
+                    Location::Empty => {}
                 }
 
             }
         }
+
+        self.dressing.tris(&mut tris, self.width, self.height, size, self.tick);
+        self.particles.tris(&mut tris, size);
         
-        let cam_offsets = self.floaty_camera.offsets((self.tick as f32) / (self.interval_frames as f32));
-        draw::transform_draw_tris(&mut tris, cam_offsets)
+        let board = self.timeline.sample(Target::Board);
+        let board_center = vec3(self.width as f32 * size / 2.0, self.height as f32 * size / 2.0, 0.0);
+        board.apply(&mut tris, board_center, 1.0);
+
+        let mut cam_offsets = self.floaty_camera.offsets(self.tick, self.floatiness);
+        cam_offsets.translation = cam_offsets.translation + self.timeline.sample(Target::Camera).position;
+        (tris, cam_offsets)
+    }
+
+    fn animate_cell(&self, verts: &mut [vdp::Vertex], x: u16, y: u16, size: f32) {
+        let target = Target::Cell(x, y);
+        if !self.timeline.is_playing(target) {
+            return
+        }
+        let origin = vec3((x as f32 + 0.5) * size, (y as f32 + 0.5) * size, 0.0);
+        self.timeline.sample(target).apply(verts, origin, size);
+    }
+}
+
+// middle of a cell in cell units, z is how far up from the floor
+fn cell_center(x: u16, y: u16, z: f32) -> Vector3 {
+    vec3(x as f32 + 0.5, y as f32 + 0.5, z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rng::Rng;
+
+    static SIZES: [[u16; 2]; 5] = [[15, 17], [16, 16], [255, 255], [256, 1], [64, 48]];
+
+    fn game(width: u16, height: u16, seeds: [u8; 2]) -> Game {
+        Game::with_rng(width, height, (width / 2).max(1), (height / 2).max(1), 0, Rng::from_seeds(seeds))
+    }
+
+    fn moved(game: &mut Game) -> bool {
+        matches!(game.tick(), TickResult::Continue)
+    }
+
+    #[test]
+    fn boards_of_any_size() {
+        for &[width, height] in SIZES.iter() {
+            let mut game = game(width, height, [12, 34]);
+            assert!(game.cells() == width as usize * height as usize);
+            assert!(game.grid.iter().filter(|&&cell| cell == Cell::Food).count() == 1);
+
+            // all the way round one way and then the other, if there is another way
+            for _ in 0..=width {
+                assert!(moved(&mut game), "{}x{}", width, height);
+            }
+            if height > 1 {
+                game.set_direction(Direction::Up);
+                for _ in 0..=height {
+                    assert!(moved(&mut game), "{}x{}", width, height);
+                }
+            }
+            // no interval between moves, the camera still keeps time
+            let (_, camera) = game.scene();
+            assert!(camera.translation.x.is_finite() && camera.rotation.x.is_finite());
+        }
+    }
+
+    #[test]
+    fn head_wraps_across_every_edge() {
+        for &[width, height] in SIZES.iter().filter(|size| size[1] > 1) {
+            for &direction in [Direction::Up, Direction::Down, Direction::Left, Direction::Right].iter() {
+                let mut game = game(width, height, [12, 34]);
+                // it starts off going right, so it has to turn once before it can go left
+                if direction == Direction::Left {
+                    game.set_direction(Direction::Up);
+                    assert!(moved(&mut game));
+                }
+                game.set_direction(direction);
+
+                let mut wrapped = false;
+                for _ in 0..=max(width, height) {
+                    let from = game.head;
+                    assert!(moved(&mut game));
+                    let to = game.head;
+                    wrapped |= match direction {
+                        Direction::Up => from[1] == height - 1 && to[1] == 0 && to[0] == from[0],
+                        Direction::Down => from[1] == 0 && to[1] == height - 1 && to[0] == from[0],
+                        Direction::Left => from[0] == 0 && to[0] == width - 1 && to[1] == from[1],
+                        Direction::Right => from[0] == width - 1 && to[0] == 0 && to[1] == from[1],
+                    };
+                }
+                assert!(wrapped, "{}x{} going {}", width, height, direction as u8);
+            }
+        }
+    }
+
+    // heads for the food until the snake is size long, then keeps going
+    fn steer(game: &mut Game, size: u32) {
+        if game.size >= size {
+            return
+        }
+        if let Some(index) = game.grid.iter().position(|&cell| cell == Cell::Food) {
+            let x = (index % game.width as usize) as u16;
+            let y = (index / game.width as usize) as u16;
+            let direction = if x > game.head[0] {
+                Direction::Right
+            } else if x < game.head[0] {
+                Direction::Left
+            } else if y > game.head[1] {
+                Direction::Up
+            } else {
+                Direction::Down
+            };
+            game.set_direction(direction);
+        }
+    }
+
+    // body is newest first, so the segment right behind the head has the most moves left and the tail has 1
+    fn check_body(game: &Game) {
+        let body = game.body();
+        assert!(body.len() + 1 == game.size as usize);
+        assert!(game.at(game.head[0], game.head[1]) == Location::Head(game.size));
+        for (i, pos) in body.iter().enumerate() {
+            assert!(game.at(pos[0], pos[1]) == Location::Body((body.len() - i) as u32), "segment {} of {}", i, body.len());
+        }
+        let occupied = (0..game.width).flat_map(|x| (0..game.height).map(move |y| (x, y)))
+            .filter(|&(x, y)| matches!(game.at(x, y), Location::Body(_)))
+            .count();
+        assert!(occupied == body.len());
+    }
+
+    #[test]
+    fn body_and_grid_agree() {
+        let mut game = game(8, 6, [12, 34]);
+        let mut ate = 0;
+        let mut wrapped = 0;
+        let mut chased = 0;
+        let mut vacated = None;
+        check_body(&game);
+
+        // grow to 3, straight on for a while, then round and round a 2x2 square one move behind the tail
+        for i in 0..80 {
+            if i < 60 {
+                steer(&mut game, 3);
+            } else {
+                game.turn(Turn::Left);
+            }
+            let from = game.head;
+            let tail = game.body().back().copied();
+            assert!(moved(&mut game), "died on move {}", i);
+            check_body(&game);
+
+            // moving into the cell the tail left on the last move
+            if vacated == Some(game.head) {
+                chased += 1;
+            }
+            vacated = tail.filter(|&tail| game.at(tail[0], tail[1]) == Location::Empty);
+
+            ate += game.drain_events().iter().filter(|&&event| event == Event::Eat).count();
+            if (from[0] as i32 - game.head[0] as i32).abs() + (from[1] as i32 - game.head[1] as i32).abs() > 1 {
+                wrapped += 1;
+            }
+        }
+        assert!(ate >= 2 && wrapped > 0 && chased > 0, "ate {}, wrapped {}, chased {}", ate, wrapped, chased);
+    }
+
+    // square boards place food with the same step of the rng as Rng::random, like before boards could be wider than they are tall
+    #[test]
+    fn square_boards_place_food_like_rng_random() {
+        for &size in [16, 255].iter() {
+            let mut game = game(size, size, [12, 34]);
+            for _ in 0..20 {
+                game.grid = vec![Cell::Empty; game.cells()];
+                let pos = Rng::from_seeds(game.rng.current_seeds()).random(size as u8);
+                assert!(game.new_food().is_ok());
+                assert!(game.get(pos[0] as u16, pos[1] as u16) == Cell::Food);
+            }
+        }
     }
 }
@@ -0,0 +1,13 @@
+/*
+all audio is synthesised at startup, there are no recorded samples
+    synth.rs turns lists of tones into PCM, it doesn't touch the console so it also works on a host
+    sfx.rs has the sound effects and plays them through dbsdk_rs::audio
+    song.rs reads the text format songs are written in, songs/ has the songs themselves
+    music.rs plays a song, in time with the game
+*/
+pub mod synth;
+pub mod sfx;
+pub mod song;
+pub mod music;
+
+pub static THEME: &str = include_str!("songs/theme.txt");
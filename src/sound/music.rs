@@ -0,0 +1,152 @@
+use dbsdk_rs::audio::{self, AudioSample, AudioVoiceParam};
+
+use super::song::{Cell, Song};
+use super::synth::{self, LOOP_FREQUENCY, SAMPLE_RATE};
+
+// rows are queued this far ahead of the audio clock, enough to cover a dropped frame or two
+static LOOKAHEAD: f64 = 0.1;
+// envelopes are played as steps in volume this far apart
+static ENVELOPE_STEP: f64 = 1.0 / 120.0;
+
+/*
+plays a Song, one voice per channel starting from first_voice
+    every channel loops a single cycle of its wave and notes just change the pitch
+    call update every frame, it queues whatever rows start before the lookahead runs out
+    the length of a row comes from the game's interval_frames, so the music keeps time with the snake
+*/
+pub struct Music {
+    song: Song,
+    samples: Vec<AudioSample>,
+    first_voice: i32,
+    // volume each channel's envelope is at, so a release starts from the right level
+    levels: Vec<f32>,
+
+    order: usize,
+    row: usize,
+    next_row: f64,
+    row_length: f64,
+    playing: bool,
+    pub volume: f32,
+}
+
+impl Music {
+    // None if the samples couldn't be created
+    pub fn new(song: Song, first_voice: i32) -> Option<Music> {
+        let mut samples = Vec::with_capacity(song.channels.len());
+        for channel in song.channels.iter() {
+            samples.push(AudioSample::create_s16(&synth::looped(channel.wave), SAMPLE_RATE).ok()?);
+        }
+
+        let mut music = Music {
+            levels: vec![0.0; song.channels.len()],
+            song,
+            samples,
+            first_voice,
+
+            order: 0,
+            row: 0,
+            next_row: 0.0,
+            row_length: 0.0,
+            playing: false,
+            volume: 1.0,
+        };
+        music.set_tempo(8);
+        Some(music)
+    }
+
+    // the game moves once every interval_frames + 1 vsyncs, at 60 a second
+    pub fn set_tempo(&mut self, interval_frames: u32) {
+        self.row_length = (interval_frames + 1) as f64 / 60.0 / self.song.rows_per_step as f64;
+    }
+
+    pub fn play(&mut self) {
+        if self.playing {
+            return
+        }
+        self.playing = true;
+        self.order = 0;
+        self.row = 0;
+        self.next_row = audio::get_time();
+    }
+
+    pub fn stop(&mut self) {
+        if !self.playing {
+            return
+        }
+        self.playing = false;
+        let time = audio::get_time();
+        for i in 0..self.song.channels.len() {
+            audio::queue_stop_voice(self.first_voice + i as i32, time);
+            self.levels[i] = 0.0;
+        }
+    }
+
+    pub fn update(&mut self) {
+        if !self.playing {
+            return
+        }
+
+        // if we fell behind (a long frame, or the clock jumped) skip ahead instead of queueing notes in the past
+        let now = audio::get_time();
+        if self.next_row < now {
+            self.next_row = now;
+        }
+
+        while self.next_row < now + LOOKAHEAD {
+            let row = self.song.patterns[self.song.order[self.order]].rows.get(self.row).cloned();
+            if let Some(row) = row {
+                for (channel, cell) in row.iter().enumerate() {
+                    self.queue_cell(channel, *cell, self.next_row);
+                }
+                self.next_row += self.row_length;
+            }
+
+            self.row += 1;
+            if self.row >= self.song.patterns[self.song.order[self.order]].rows.len() {
+                self.row = 0;
+                self.order = (self.order + 1) % self.song.order.len();
+            }
+        }
+    }
+
+    fn queue_cell(&mut self, channel: usize, cell: Cell, time: f64) {
+        let slot = self.first_voice + channel as i32;
+        let envelope = self.song.channels[channel].envelope;
+        let peak = self.song.channels[channel].volume * self.volume;
+
+        match cell {
+            Cell::Hold => {}
+            Cell::Note(frequency) => {
+                let sample = &self.samples[channel];
+                audio::queue_stop_voice(slot, time);
+                audio::queue_set_voice_param_i(slot, AudioVoiceParam::SampleData, sample.handle, time);
+                audio::queue_set_voice_param_i(slot, AudioVoiceParam::Samplerate, sample.samplerate, time);
+                audio::queue_set_voice_param_i(slot, AudioVoiceParam::LoopEnabled, 1, time);
+                audio::queue_set_voice_param_f(slot, AudioVoiceParam::Pitch, frequency / LOOP_FREQUENCY, time);
+                audio::queue_set_voice_param_f(slot, AudioVoiceParam::Volume, 0.0, time);
+                audio::queue_start_voice(slot, time);
+
+                let attack = envelope.attack as f64;
+                ramp(slot, 0.0, peak, time, attack);
+                ramp(slot, peak, peak * envelope.sustain, time + attack, envelope.decay as f64);
+                self.levels[channel] = peak * envelope.sustain;
+            }
+            Cell::Off => {
+                let release = envelope.release as f64;
+                ramp(slot, self.levels[channel], 0.0, time, release);
+                audio::queue_stop_voice(slot, time + release);
+                self.levels[channel] = 0.0;
+            }
+        }
+    }
+}
+
+// queues volume changes from start to start + length, ending exactly on to
+fn ramp(slot: i32, from: f32, to: f32, start: f64, length: f64) {
+    let steps = (length / ENVELOPE_STEP).ceil().max(1.0) as u32;
+    for step in 1..=steps {
+        let t = step as f32 / steps as f32;
+        let time = start + length * step as f64 / steps as f64;
+        audio::queue_set_voice_param_f(slot, AudioVoiceParam::Volume, from + (to - from) * t, time);
+    }
+}
@@ -0,0 +1,120 @@
+use dbsdk_rs::audio::{self, AudioSample, AudioVoiceParam};
+
+use snake::Event;
+
+use super::synth::{self, Envelope, Tone, Wave, SAMPLE_RATE};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Sfx {
+    Eat,
+    Turn,
+    Death,
+    PowerUp,
+    MenuMove,
+    MenuSelect,
+}
+
+pub static ALL: [Sfx; 6] = [Sfx::Eat, Sfx::Turn, Sfx::Death, Sfx::PowerUp, Sfx::MenuMove, Sfx::MenuSelect];
+
+impl Sfx {
+    pub fn from_event(event: Event) -> Sfx {
+        match event {
+            Event::Turn => Sfx::Turn,
+            Event::Eat => Sfx::Eat,
+            Event::Death => Sfx::Death,
+            // filling the board is the closest thing the game has to a power up
+            Event::Win => Sfx::PowerUp,
+        }
+    }
+
+    // frequencies are in hz, times in seconds
+    pub fn tones(self) -> Vec<Tone> {
+        match self {
+            // two quick rising blips
+            Sfx::Eat => vec![
+                Tone::new(Wave::Square(0.5), 660.0, 0.05).volume(0.5),
+                Tone::new(Wave::Square(0.5), 990.0, 0.07).at(0.05).volume(0.5),
+            ],
+            // barely there, it plays on every turn
+            Sfx::Turn => vec![
+                Tone::new(Wave::Triangle, 1400.0, 0.025).sweep(900.0).volume(0.35),
+            ],
+            // falling buzz under a burst of noise
+            Sfx::Death => vec![
+                Tone::new(Wave::Square(0.25), 220.0, 0.45).sweep(40.0).envelope(Envelope::new(0.002, 0.1, 0.6, 0.3)).volume(0.45),
+                Tone::new(Wave::Noise, 3000.0, 0.5).sweep(400.0).envelope(Envelope::new(0.002, 0.15, 0.4, 0.3)).volume(0.5),
+            ],
+            // major arpeggio, C E G C
+            Sfx::PowerUp => vec![
+                Tone::new(Wave::Square(0.125), 523.25, 0.08).volume(0.4),
+                Tone::new(Wave::Square(0.125), 659.25, 0.08).at(0.08).volume(0.4),
+                Tone::new(Wave::Square(0.125), 783.99, 0.08).at(0.16).volume(0.4),
+                Tone::new(Wave::Square(0.125), 1046.5, 0.25).at(0.24).envelope(Envelope::new(0.002, 0.05, 0.7, 0.15)).volume(0.4),
+                Tone::new(Wave::Triangle, 1046.5, 0.4).at(0.24).envelope(Envelope::pluck(0.3)).volume(0.4),
+            ],
+            Sfx::MenuMove => vec![
+                Tone::new(Wave::Square(0.5), 880.0, 0.03).volume(0.3),
+            ],
+            Sfx::MenuSelect => vec![
+                Tone::new(Wave::Square(0.5), 880.0, 0.04).volume(0.35),
+                Tone::new(Wave::Square(0.5), 1320.0, 0.08).at(0.04).volume(0.35),
+            ],
+        }
+    }
+}
+
+/*
+every effect rendered and uploaded once at startup, played on a small pool of voices
+    voices are handed out round robin, so a new sound cuts off the oldest one still playing
+    the rest of the 32 voices are left alone for music
+*/
+pub struct SoundEffects {
+    samples: Vec<AudioSample>,
+    first_voice: i32,
+    voice_count: i32,
+    next_voice: i32,
+    pub volume: f32,
+}
+
+impl SoundEffects {
+    // None if the samples couldn't be created
+    pub fn new(first_voice: i32, voice_count: i32) -> Option<SoundEffects> {
+        let mut samples = Vec::with_capacity(ALL.len());
+        for sfx in ALL.iter() {
+            samples.push(AudioSample::create_s16(&synth::render(&sfx.tones()), SAMPLE_RATE).ok()?);
+        }
+
+        Some(SoundEffects {
+            samples,
+            first_voice,
+            voice_count,
+            next_voice: 0,
+            volume: 1.0,
+        })
+    }
+
+    pub fn play(&mut self, sfx: Sfx) {
+        if self.volume <= 0.0 {
+            return
+        }
+
+        let sample = &self.samples[sfx as usize];
+        let slot = self.first_voice + self.next_voice;
+        self.next_voice = (self.next_voice + 1) % self.voice_count;
+
+        let time = audio::get_time();
+        audio::queue_stop_voice(slot, time);
+        audio::queue_set_voice_param_i(slot, AudioVoiceParam::SampleData, sample.handle, time);
+        audio::queue_set_voice_param_i(slot, AudioVoiceParam::Samplerate, sample.samplerate, time);
+        audio::queue_set_voice_param_i(slot, AudioVoiceParam::LoopEnabled, 0, time);
+        audio::queue_set_voice_param_f(slot, AudioVoiceParam::Pitch, 1.0, time);
+        audio::queue_set_voice_param_f(slot, AudioVoiceParam::Volume, self.volume, time);
+        audio::queue_start_voice(slot, time);
+    }
+
+    pub fn play_events(&mut self, events: &[Event]) {
+        for event in events.iter() {
+            self.play(Sfx::from_event(*event));
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use super::synth::{Envelope, Wave};
+
+/*
+songs are plain text so they can be written by hand and compiled in with include_str!
+
+    // comments start with two slashes
+    rows_per_step 2
+    channel lead square25 0.3 0.005 0.1 0.5 0.05
+    channel bass triangle 0.5 0.005 0.05 0.8 0.03
+    pattern intro
+    C-5 C-3
+    ... ...
+    === G-2
+    end
+    order intro intro
+
+rows_per_step is how many rows play for every move the snake makes, so the tempo follows the game speed
+channel <name> <wave> <volume> <attack> <decay> <sustain> <release>, in the order the columns appear
+    waves are square50, square25, square12, triangle, saw, sine and noise
+    the envelope is the same as synth::Envelope, times are in seconds
+pattern rows have one column per channel
+    a note like C-4 or F#2 starts a note, noise uses the pitch too
+    ... leaves the channel alone, === releases the note that's playing
+order is the list of patterns to play, it loops back to the start at the end
+*/
+pub struct Song {
+    pub rows_per_step: u32,
+    pub channels: Vec<Channel>,
+    pub patterns: Vec<Pattern>,
+    pub order: Vec<usize>,
+}
+
+pub struct Channel {
+    pub name: String,
+    pub wave: Wave,
+    pub volume: f32,
+    pub envelope: Envelope,
+}
+
+pub struct Pattern {
+    pub name: String,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Cell {
+    Hold,
+    Note(f32), // hz
+    Off,
+}
+
+impl Song {
+    // errors say which line was wrong, starting from 1
+    pub fn parse(text: &str) -> Result<Song, String> {
+        let mut song = Song {
+            rows_per_step: 1,
+            channels: Vec::new(),
+            patterns: Vec::new(),
+            order: Vec::new(),
+        };
+        let mut pattern: Option<Pattern> = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let error = |msg: &str| Err(format!("line {}: {}", number + 1, msg));
+
+            let line = match line.find("//") {
+                Some(comment) => &line[..comment],
+                None => line,
+            };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+
+            if let Some(current) = pattern.as_mut() {
+                if words[0] == "end" {
+                    song.patterns.push(pattern.take().unwrap());
+                    continue;
+                }
+                if words.len() != song.channels.len() {
+                    return error(&format!("expected {} columns, found {}", song.channels.len(), words.len()));
+                }
+                let mut row = Vec::with_capacity(words.len());
+                for word in words.iter() {
+                    match parse_cell(word) {
+                        Some(cell) => row.push(cell),
+                        None => return error(&format!("'{}' isn't a note", word)),
+                    }
+                }
+                current.rows.push(row);
+                continue;
+            }
+
+            match words[0] {
+                "rows_per_step" if words.len() == 2 => {
+                    song.rows_per_step = match words[1].parse() {
+                        Ok(rows) if rows > 0 => rows,
+                        _ => return error("rows_per_step should be a whole number above 0"),
+                    };
+                }
+                "channel" if words.len() == 8 => {
+                    if !song.patterns.is_empty() {
+                        return error("channels have to come before any patterns");
+                    }
+                    let wave = match parse_wave(words[2]) {
+                        Some(wave) => wave,
+                        None => return error(&format!("'{}' isn't a wave", words[2])),
+                    };
+                    let mut numbers = [0.0; 5];
+                    for (i, word) in words[3..].iter().enumerate() {
+                        numbers[i] = match word.parse() {
+                            Ok(number) => number,
+                            Err(_) => return error(&format!("'{}' isn't a number", word)),
+                        };
+                    }
+                    song.channels.push(Channel {
+                        name: words[1].to_owned(),
+                        wave,
+                        volume: numbers[0],
+                        envelope: Envelope::new(numbers[1], numbers[2], numbers[3], numbers[4]),
+                    });
+                }
+                "pattern" if words.len() == 2 => {
+                    pattern = Some(Pattern {
+                        name: words[1].to_owned(),
+                        rows: Vec::new(),
+                    });
+                }
+                "order" => {
+                    for name in words[1..].iter() {
+                        match song.patterns.iter().position(|p| p.name == *name) {
+                            Some(index) => song.order.push(index),
+                            None => return error(&format!("no pattern called '{}'", name)),
+                        }
+                    }
+                }
+                _ => return error(&format!("don't know what to do with '{}'", line.trim())),
+            }
+        }
+
+        if pattern.is_some() {
+            return Err("last pattern is missing its end".to_owned())
+        }
+        if song.order.is_empty() || song.order.iter().all(|&i| song.patterns[i].rows.is_empty()) {
+            return Err("nothing to play, order is empty".to_owned())
+        }
+        Ok(song)
+    }
+}
+
+fn parse_wave(word: &str) -> Option<Wave> {
+    match word {
+        "square50" => Some(Wave::Square(0.5)),
+        "square25" => Some(Wave::Square(0.25)),
+        "square12" => Some(Wave::Square(0.125)),
+        "triangle" => Some(Wave::Triangle),
+        "saw" => Some(Wave::Saw),
+        "sine" => Some(Wave::Sine),
+        "noise" => Some(Wave::Noise),
+        _ => None,
+    }
+}
+
+// C-4 is middle C, A-4 is 440hz
+fn parse_cell(word: &str) -> Option<Cell> {
+    match word {
+        "..." => return Some(Cell::Hold),
+        "===" => return Some(Cell::Off),
+        _ => {}
+    }
+
+    let bytes = word.as_bytes();
+    if bytes.len() != 3 {
+        return None
+    }
+    let semitone = match bytes[0] {
+        b'C' => 0,
+        b'D' => 2,
+        b'E' => 4,
+        b'F' => 5,
+        b'G' => 7,
+        b'A' => 9,
+        b'B' => 11,
+        _ => return None,
+    };
+    let sharp = match bytes[1] {
+        b'-' => 0,
+        b'#' => 1,
+        _ => return None,
+    };
+    let octave = match bytes[2] {
+        b'0'..=b'9' => (bytes[2] - b'0') as i32,
+        _ => return None,
+    };
+
+    let midi = (octave + 1) * 12 + semitone + sharp;
+    Some(Cell::Note(440.0 * 2.0f32.powf((midi - 69) as f32 / 12.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sound::THEME;
+
+    fn error(text: &str) -> String {
+        match Song::parse(text) {
+            Ok(_) => panic!("expected an error for {:?}", text),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn theme() {
+        let song = match Song::parse(THEME) {
+            Ok(song) => song,
+            Err(error) => panic!("theme.txt doesn't parse, {}", error),
+        };
+        assert!(!song.channels.is_empty());
+        assert!(song.order.iter().all(|&i| i < song.patterns.len()));
+    }
+
+    #[test]
+    fn notes() {
+        let song = Song::parse("channel lead sine 1 0 0 1 0\npattern a\nA-4 // a comment\nC-4\n...\n===\nend\norder a a").unwrap();
+        let rows = &song.patterns[0].rows;
+        assert!(rows[0][0] == Cell::Note(440.0));
+        match rows[1][0] {
+            Cell::Note(hz) => assert!((hz - 261.63).abs() < 0.01),
+            _ => panic!("C-4 should be a note"),
+        }
+        assert!(rows[2][0] == Cell::Hold && rows[3][0] == Cell::Off);
+        assert!(song.order == [0, 0]);
+    }
+
+    #[test]
+    fn errors() {
+        let channel = "channel lead sine 1 0 0 1 0\n";
+        assert!(error(&format!("{}pattern a\nH-4\nend\norder a", channel)) == "line 3: 'H-4' isn't a note");
+        assert!(error(&format!("{}pattern a\nC-4 C-4\nend\norder a", channel)) == "line 3: expected 1 columns, found 2");
+        assert!(error(&format!("{}pattern a\nC-4\nend\norder b", channel)) == "line 5: no pattern called 'b'");
+        assert!(error(&format!("{}pattern a\nC-4\n", channel)) == "last pattern is missing its end");
+        assert!(error(channel) == "nothing to play, order is empty");
+        assert!(error("channel lead organ 1 0 0 1 0") == "line 1: 'organ' isn't a wave");
+        assert!(error("rows_per_step 0") == "line 1: rows_per_step should be a whole number above 0");
+        assert!(error("tempo 120") == "line 1: don't know what to do with 'tempo 120'");
+    }
+}
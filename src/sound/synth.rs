@@ -0,0 +1,205 @@
+use std::f32::consts::PI;
+
+use crate::animation::track::Lerp;
+
+// everything is generated at this rate, plenty for chiptune-y effects and keeps the samples small
+pub static SAMPLE_RATE: i32 = 22050;
+
+#[derive(Clone, Copy)]
+pub enum Wave {
+    Square(f32), // duty cycle, 0.5 is a plain square
+    Triangle,
+    Saw,
+    Sine,
+    Noise,
+}
+
+/*
+linear ADSR, times are in seconds
+    sustain is a level, it's held until the tone's duration minus the release
+*/
+#[derive(Clone, Copy)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Envelope {
+    pub const fn new(attack: f32, decay: f32, sustain: f32, release: f32) -> Envelope {
+        Envelope {
+            attack,
+            decay,
+            sustain,
+            release,
+        }
+    }
+
+    // straight up, straight down, for blips
+    pub const fn pluck(release: f32) -> Envelope {
+        Envelope::new(0.002, 0.0, 1.0, release)
+    }
+
+    // level at time t into a tone that lasts duration
+    pub fn level(&self, t: f32, duration: f32) -> f32 {
+        let release_start = (duration - self.release).max(0.0);
+        let held = if t < self.attack {
+            t / self.attack
+        } else if t < self.attack + self.decay {
+            Lerp::lerp(1.0, self.sustain, (t - self.attack) / self.decay)
+        } else {
+            self.sustain
+        };
+
+        if t < release_start {
+            held
+        } else if self.release > 0.0 {
+            held * (1.0 - (t - release_start) / self.release).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/*
+one note of an effect
+    frequency sweeps linearly from [start, end] over the duration
+    offset is when it starts in seconds, so effects can be a few tones in a row or on top of each other
+*/
+#[derive(Clone, Copy)]
+pub struct Tone {
+    pub wave: Wave,
+    pub frequency: [f32; 2],
+    pub offset: f32,
+    pub duration: f32,
+    pub envelope: Envelope,
+    pub volume: f32,
+}
+
+impl Tone {
+    pub const fn new(wave: Wave, frequency: f32, duration: f32) -> Tone {
+        Tone {
+            wave,
+            frequency: [frequency, frequency],
+            offset: 0.0,
+            duration,
+            envelope: Envelope::pluck(0.01),
+            volume: 1.0,
+        }
+    }
+
+    pub const fn sweep(mut self, to: f32) -> Tone {
+        self.frequency[1] = to;
+        self
+    }
+
+    pub const fn at(mut self, offset: f32) -> Tone {
+        self.offset = offset;
+        self
+    }
+
+    pub const fn envelope(mut self, envelope: Envelope) -> Tone {
+        self.envelope = envelope;
+        self
+    }
+
+    pub const fn volume(mut self, volume: f32) -> Tone {
+        self.volume = volume;
+        self
+    }
+}
+
+// 15 bit LFSR like the NES noise channel, always starts from the same state so renders are repeatable
+pub struct Noise {
+    state: u16,
+}
+
+impl Default for Noise {
+    fn default() -> Noise {
+        Noise::new()
+    }
+}
+
+impl Noise {
+    pub fn new() -> Noise {
+        Noise {
+            state: 1,
+        }
+    }
+
+    pub fn sample(&mut self) -> f32 {
+        let bit = (self.state ^ (self.state >> 1)) & 1;
+        self.state = (self.state >> 1) | (bit << 14);
+        if self.state & 1 == 1 { 1.0 } else { -1.0 }
+    }
+}
+
+// one cycle of a wave, phase is 0 to 1
+pub fn oscillate(wave: Wave, phase: f32, noise: &mut Noise) -> f32 {
+    match wave {
+        Wave::Square(duty) => if phase < duty { 1.0 } else { -1.0 },
+        Wave::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        Wave::Saw => phase * 2.0 - 1.0,
+        Wave::Sine => (phase * 2.0 * PI).sin(),
+        Wave::Noise => noise.sample(),
+    }
+}
+
+// mixes the tones into signed 16 bit PCM at SAMPLE_RATE, clipped rather than normalized so volumes stay comparable
+pub fn render(tones: &[Tone]) -> Vec<i16> {
+    let length = tones.iter().map(|t| t.offset + t.duration).fold(0.0, f32::max);
+    let mut mix = vec![0.0f32; (length * SAMPLE_RATE as f32).ceil() as usize];
+
+    for tone in tones.iter() {
+        let mut noise = Noise::new();
+        let mut phase = 0.0;
+        // noise is clocked at the tone's frequency, so it holds each value for a few samples at lower pitches
+        let mut held = noise.sample();
+
+        let start = (tone.offset * SAMPLE_RATE as f32) as usize;
+        let count = (tone.duration * SAMPLE_RATE as f32) as usize;
+        for i in 0..count {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let frequency: f32 = Lerp::lerp(tone.frequency[0], tone.frequency[1], t / tone.duration);
+
+            let value = match tone.wave {
+                Wave::Noise => held,
+                wave => oscillate(wave, phase, &mut noise),
+            };
+            mix[start + i] += value * tone.envelope.level(t, tone.duration) * tone.volume;
+
+            phase += frequency / SAMPLE_RATE as f32;
+            if phase >= 1.0 {
+                phase -= phase.floor();
+                if let Wave::Noise = tone.wave {
+                    held = noise.sample();
+                }
+            }
+        }
+    }
+
+    mix.iter().map(|v| (v.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).collect()
+}
+
+// length of the looping samples music is played with, the pitch of a note is its frequency over LOOP_FREQUENCY
+pub static LOOP_LENGTH: usize = 64;
+pub static LOOP_FREQUENCY: f32 = SAMPLE_RATE as f32 / LOOP_LENGTH as f32;
+
+/*
+a sample meant to be looped
+    one cycle of the wave for anything tonal
+    noise needs a lot more than one cycle to not sound like a buzz, so it gets a long run of values instead
+*/
+pub fn looped(wave: Wave) -> Vec<i16> {
+    let mut noise = Noise::new();
+    let length = match wave {
+        Wave::Noise => LOOP_LENGTH * 128,
+        _ => LOOP_LENGTH,
+    };
+
+    (0..length).map(|i| {
+        let phase = (i % LOOP_LENGTH) as f32 / LOOP_LENGTH as f32;
+        (oscillate(wave, phase, &mut noise) * 0.8 * i16::MAX as f32) as i16
+    }).collect()
+}
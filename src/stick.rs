@@ -0,0 +1,284 @@
+use snake::Direction;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DeadzoneShape {
+    Radial, // a circle, the stick's distance from the middle has to pass the deadzone
+    Axial,  // a cross, each axis is checked on its own, closer to how the original read_deadzone behaved
+}
+
+impl DeadzoneShape {
+    pub fn name(self) -> &'static str {
+        match self {
+            DeadzoneShape::Radial => "RADIAL",
+            DeadzoneShape::Axial => "AXIAL",
+        }
+    }
+}
+
+/*
+how a stick gets turned into a direction
+    inner is the deadzone, anything closer to the middle reads as 0
+    outer is where the stick counts as pushed all the way, worn sticks often can't reach the edge
+    hysteresis is in degrees, a direction is kept until the stick is this far past the diagonal
+    a flick is the stick going from inside the deadzone to past flick_distance in at most flick_ticks frames
+*/
+#[derive(Clone, Copy)]
+pub struct StickConfig {
+    pub shape: DeadzoneShape,
+    pub inner: f32,
+    pub outer: f32,
+    pub hysteresis: f32,
+    pub flick_distance: f32,
+    pub flick_ticks: u32,
+}
+
+impl StickConfig {
+    pub fn classic(shape: DeadzoneShape, inner: f32) -> StickConfig {
+        StickConfig {
+            shape,
+            inner,
+            outer: 0.95,
+            hysteresis: 12.0,
+            flick_distance: 0.85,
+            flick_ticks: 5,
+        }
+    }
+}
+
+// -1 to 1, without the deadzone
+pub fn normalize(x: i16, y: i16) -> [f32; 2] {
+    [
+        (x as f32 / i16::MAX as f32).max(-1.0),
+        (y as f32 / i16::MAX as f32).max(-1.0),
+    ]
+}
+
+// rescales so the edge of the deadzone is 0 and outer is 1
+fn remap(value: f32, inner: f32, outer: f32) -> f32 {
+    if value <= inner {
+        0.0
+    } else {
+        ((value - inner) / (outer - inner).max(0.0001)).min(1.0)
+    }
+}
+
+// the deadzone and saturation applied, still -1 to 1 on each axis
+pub fn apply_deadzone(config: &StickConfig, value: [f32; 2]) -> [f32; 2] {
+    match config.shape {
+        DeadzoneShape::Radial => {
+            let length = (value[0] * value[0] + value[1] * value[1]).sqrt();
+            if length <= config.inner {
+                return [0.0, 0.0]
+            }
+            let scale = remap(length, config.inner, config.outer) / length;
+            [value[0] * scale, value[1] * scale]
+        }
+        DeadzoneShape::Axial => [
+            remap(value[0].abs(), config.inner, config.outer).copysign(value[0]),
+            remap(value[1].abs(), config.inner, config.outer).copysign(value[1]),
+        ],
+    }
+}
+
+// the closest of the four directions, ignoring hysteresis
+pub fn snap(value: [f32; 2]) -> Option<Direction> {
+    if value[0] == 0.0 && value[1] == 0.0 {
+        return None
+    }
+    if value[0].abs() > value[1].abs() {
+        Some(if value[0] > 0.0 { Direction::Right } else { Direction::Left })
+    } else {
+        Some(if value[1] > 0.0 { Direction::Up } else { Direction::Down })
+    }
+}
+
+// degrees between the stick and a direction, 0 to 180
+fn angle_from(value: [f32; 2], direction: Direction) -> f32 {
+    let angle = value[1].atan2(value[0]).to_degrees();
+    let target = match direction {
+        Direction::Right => 0.0,
+        Direction::Up => 90.0,
+        Direction::Left => 180.0,
+        Direction::Down => -90.0,
+    };
+    let difference = (angle - target).abs() % 360.0;
+    if difference > 180.0 { 360.0 - difference } else { difference }
+}
+
+/*
+reads one stick frame by frame
+    direction is where the stick is pointing, it only changes when the stick is clearly in another direction's
+    quarter so holding a diagonal doesn't flicker between two
+    flick is only set on the frame a flick finishes
+*/
+pub struct StickReader {
+    pub config: StickConfig,
+    pub value: [f32; 2],
+    pub direction: Option<Direction>,
+    pub last_direction: Option<Direction>,
+    pub flick: Option<Direction>,
+    // frames since the stick was last inside the deadzone
+    ticks_out: u32,
+}
+
+impl StickReader {
+    pub fn new(config: StickConfig) -> StickReader {
+        StickReader {
+            config,
+            value: [0.0, 0.0],
+            direction: None,
+            last_direction: None,
+            flick: None,
+            ticks_out: 0,
+        }
+    }
+
+    pub fn update(&mut self, x: i16, y: i16) {
+        let raw = normalize(x, y);
+        self.value = apply_deadzone(&self.config, raw);
+        self.last_direction = self.direction;
+
+        self.direction = match (self.direction, snap(self.value)) {
+            (_, None) => None,
+            // 45 degrees is the diagonal, the current direction holds on until it's passed by the hysteresis
+            (Some(current), Some(_)) if angle_from(self.value, current) <= 45.0 + self.config.hysteresis => Some(current),
+            (_, closest) => closest,
+        };
+
+        self.flick = None;
+        if self.value == [0.0, 0.0] {
+            self.ticks_out = 0;
+            return
+        }
+        self.ticks_out += 1;
+
+        let length = (raw[0] * raw[0] + raw[1] * raw[1]).sqrt();
+        if self.ticks_out <= self.config.flick_ticks && length >= self.config.flick_distance {
+            self.flick = snap(self.value);
+            // only once per push
+            self.ticks_out = self.config.flick_ticks + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dbsdk_rs::gamepad::GamepadState;
+    use input::empty_state;
+
+    // the left stick at angle degrees from right, counterclockwise, length from the middle
+    fn state(angle: f32, length: f32) -> GamepadState {
+        let mut state = empty_state();
+        let radians = angle.to_radians();
+        state.left_stick_x = (radians.cos() * length * i16::MAX as f32) as i16;
+        state.left_stick_y = (radians.sin() * length * i16::MAX as f32) as i16;
+        state
+    }
+
+    fn read(reader: &mut StickReader, state: GamepadState) {
+        reader.update(state.left_stick_x, state.left_stick_y);
+    }
+
+    #[test]
+    fn radial_and_axial_deadzones() {
+        let mut radial = StickReader::new(StickConfig::classic(DeadzoneShape::Radial, 0.3));
+        let mut axial = StickReader::new(StickConfig::classic(DeadzoneShape::Axial, 0.3));
+
+        // on the diagonal each axis is inside the deadzone but the length isn't
+        read(&mut radial, state(45.0, 0.4));
+        read(&mut axial, state(45.0, 0.4));
+        assert!(radial.value[0] > 0.0 && radial.value[1] > 0.0);
+        assert!(axial.value == [0.0, 0.0]);
+        assert!(axial.direction.is_none());
+
+        // along an axis they agree
+        read(&mut radial, state(0.0, 0.6));
+        read(&mut axial, state(0.0, 0.6));
+        assert!((radial.value[0] - axial.value[0]).abs() < 0.001);
+        assert!(radial.direction == Some(Direction::Right) && axial.direction == Some(Direction::Right));
+
+        read(&mut radial, state(0.0, 0.2));
+        assert!(radial.value == [0.0, 0.0]);
+    }
+
+    #[test]
+    fn saturates_at_outer() {
+        for shape in [DeadzoneShape::Radial, DeadzoneShape::Axial].iter() {
+            let mut reader = StickReader::new(StickConfig::classic(*shape, 0.2));
+            let inner = reader.config.inner;
+            let outer = reader.config.outer;
+
+            read(&mut reader, state(0.0, outer));
+            assert!((reader.value[0] - 1.0).abs() < 0.001);
+            read(&mut reader, state(0.0, 1.0));
+            assert!(reader.value[0] == 1.0);
+            read(&mut reader, state(90.0, (outer + 1.0) / 2.0));
+            assert!(reader.value[1] == 1.0);
+
+            // halfway between the deadzone and outer reads as half
+            read(&mut reader, state(180.0, (inner + outer) / 2.0));
+            assert!((reader.value[0] + 0.5).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn hysteresis_holds_past_the_diagonal() {
+        let mut reader = StickReader::new(StickConfig::classic(DeadzoneShape::Radial, 0.2));
+        let hysteresis = reader.config.hysteresis;
+
+        read(&mut reader, state(0.0, 0.8));
+        assert!(reader.direction == Some(Direction::Right));
+
+        // either side of the diagonal, and up to the hysteresis past it, it stays right
+        for angle in [44.0, 46.0, 45.0 + hysteresis - 1.0].iter() {
+            read(&mut reader, state(*angle, 0.8));
+            assert!(reader.direction == Some(Direction::Right), "switched at {}", angle);
+        }
+
+        read(&mut reader, state(45.0 + hysteresis + 1.0, 0.8));
+        assert!(reader.direction == Some(Direction::Up));
+        assert!(reader.last_direction == Some(Direction::Right));
+
+        // and up holds on the same way coming back
+        read(&mut reader, state(44.0, 0.8));
+        assert!(reader.direction == Some(Direction::Up));
+        read(&mut reader, state(45.0 - hysteresis - 1.0, 0.8));
+        assert!(reader.direction == Some(Direction::Right));
+
+        // letting go forgets the direction, the next push snaps to the closest
+        read(&mut reader, empty_state());
+        assert!(reader.direction.is_none());
+        read(&mut reader, state(50.0, 0.8));
+        assert!(reader.direction == Some(Direction::Up));
+    }
+
+    #[test]
+    fn flick_fires_once_per_push() {
+        let mut reader = StickReader::new(StickConfig::classic(DeadzoneShape::Radial, 0.2));
+
+        for _ in 0..2 {
+            read(&mut reader, empty_state());
+            assert!(reader.flick.is_none());
+
+            read(&mut reader, state(0.0, 1.0));
+            assert!(reader.flick == Some(Direction::Right));
+
+            // held out there it doesn't fire again
+            for _ in 0..10 {
+                read(&mut reader, state(0.0, 1.0));
+                assert!(reader.flick.is_none());
+            }
+        }
+
+        // taking longer than flick_ticks to get out to flick_distance isn't a flick
+        read(&mut reader, empty_state());
+        let mut length: f32 = 0.0;
+        while length < 1.0 {
+            length += 0.08;
+            read(&mut reader, state(90.0, length.min(1.0)));
+            assert!(reader.flick.is_none(), "flicked at {}", length);
+        }
+        assert!(reader.direction == Some(Direction::Up));
+    }
+}
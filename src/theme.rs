@@ -0,0 +1,235 @@
+use dbsdk_rs::{math::Vector4, vdp::Color32};
+
+use crate::backdrop::BackdropPalette;
+use crate::dressing::DressingPalette;
+use crate::floor::FloorPalette;
+
+// kinds of food, only normal food is placed so far but themes already have a color for the others
+#[derive(Clone, Copy, PartialEq)]
+pub enum FoodKind {
+    Normal,
+    Bonus,
+    Rare,
+}
+
+#[derive(Clone, Copy)]
+pub struct UiPalette {
+    pub title: Vector4,
+    pub item: Vector4,
+    pub selected: Vector4,
+    pub panel: Vector4,
+}
+
+/*
+every color the game draws with
+    body is the two colors a skin without its own uses, see skin::Skin
+    particles take their colors from the food and the snake
+*/
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color32,
+    pub floor: FloorPalette,
+    pub dressing: DressingPalette,
+    pub backdrop: BackdropPalette,
+    pub head: Vector4,
+    pub body: [Vector4; 2],
+    pub eye: Vector4,
+    pub pupil: Vector4,
+    pub food: [Vector4; 3],
+    pub ui: UiPalette,
+}
+
+impl Theme {
+    pub fn food(&self, kind: FoodKind) -> Vector4 {
+        self.food[kind as usize]
+    }
+}
+
+const fn rgb(r: f32, g: f32, b: f32) -> Vector4 {
+    Vector4::new(r, g, b, 1.0)
+}
+
+const fn grey(v: f32) -> Vector4 {
+    rgb(v, v, v)
+}
+
+// the colors the game always had
+pub static CLASSIC: Theme = Theme {
+    name: "CLASSIC",
+    background: Color32::new(0, 0, 0, 255),
+    floor: FloorPalette {
+        empty: grey(0.25),
+        occupied: grey(0.15),
+        food: [grey(0.15), rgb(0.55, 0.2, 0.2)],
+        trail: rgb(0.1, 0.35, 0.1),
+        danger: rgb(0.6, 0.3, 0.0),
+    },
+    dressing: DressingPalette {
+        portal: [rgb(0.1, 0.2, 0.5), rgb(0.4, 0.7, 1.0)],
+        grid: grey(0.1),
+        corner: grey(0.9),
+        checker: grey(0.85),
+        gradient: [grey(1.0), rgb(0.7, 0.75, 0.9)],
+    },
+    backdrop: BackdropPalette {
+        star: grey(0.9),
+        nebula: [rgb(0.03, 0.04, 0.08), rgb(0.1, 0.08, 0.2)],
+        debris: grey(0.3),
+    },
+    head: rgb(0.4, 1.0, 0.4),
+    body: [rgb(0.0, 1.0, 0.0), rgb(0.0, 0.6, 0.0)],
+    eye: grey(1.0),
+    pupil: grey(0.0),
+    food: [rgb(1.0, 0.0, 0.0), rgb(1.0, 0.8, 0.2), rgb(0.7, 0.4, 1.0)],
+    ui: UiPalette {
+        title: rgb(0.4, 1.0, 0.4),
+        item: grey(0.8),
+        selected: rgb(1.0, 0.9, 0.2),
+        panel: rgb(0.05, 0.08, 0.05),
+    },
+};
+
+pub static NEON: Theme = Theme {
+    name: "NEON",
+    background: Color32::new(6, 0, 16, 255),
+    floor: FloorPalette {
+        empty: rgb(0.07, 0.04, 0.13),
+        occupied: rgb(0.03, 0.02, 0.06),
+        food: [rgb(0.03, 0.02, 0.06), rgb(0.6, 0.05, 0.5)],
+        trail: rgb(0.0, 0.4, 0.5),
+        danger: rgb(1.0, 0.1, 0.4),
+    },
+    dressing: DressingPalette {
+        portal: [rgb(0.2, 0.0, 0.5), rgb(0.0, 1.0, 1.0)],
+        grid: rgb(0.2, 0.0, 0.35),
+        corner: rgb(0.0, 1.0, 1.0),
+        checker: rgb(0.8, 0.8, 0.9),
+        gradient: [grey(1.0), rgb(0.7, 0.5, 1.0)],
+    },
+    backdrop: BackdropPalette {
+        star: rgb(0.8, 0.9, 1.0),
+        nebula: [rgb(0.08, 0.0, 0.12), rgb(0.3, 0.0, 0.35)],
+        debris: rgb(0.2, 0.1, 0.35),
+    },
+    head: rgb(0.6, 1.0, 1.0),
+    body: [rgb(0.0, 1.0, 0.9), rgb(0.6, 0.0, 1.0)],
+    eye: grey(1.0),
+    pupil: rgb(0.3, 0.0, 0.4),
+    food: [rgb(1.0, 0.0, 0.6), rgb(1.0, 1.0, 0.0), rgb(0.2, 1.0, 0.2)],
+    ui: UiPalette {
+        title: rgb(0.0, 1.0, 1.0),
+        item: rgb(0.8, 0.7, 1.0),
+        selected: rgb(1.0, 0.2, 0.9),
+        panel: rgb(0.06, 0.02, 0.1),
+    },
+};
+
+pub static PASTEL: Theme = Theme {
+    name: "PASTEL",
+    background: Color32::new(232, 226, 242, 255),
+    floor: FloorPalette {
+        empty: rgb(0.85, 0.82, 0.9),
+        occupied: rgb(0.75, 0.72, 0.82),
+        food: [rgb(0.75, 0.72, 0.82), rgb(1.0, 0.7, 0.7)],
+        trail: rgb(0.7, 0.9, 0.75),
+        danger: rgb(1.0, 0.75, 0.5),
+    },
+    dressing: DressingPalette {
+        portal: [rgb(0.7, 0.8, 1.0), rgb(0.9, 0.95, 1.0)],
+        grid: rgb(0.7, 0.68, 0.78),
+        corner: rgb(0.55, 0.5, 0.65),
+        checker: grey(0.95),
+        gradient: [grey(1.0), rgb(0.95, 0.9, 1.0)],
+    },
+    backdrop: BackdropPalette {
+        star: grey(1.0),
+        nebula: [rgb(0.88, 0.85, 0.95), rgb(0.98, 0.85, 0.9)],
+        debris: rgb(0.78, 0.75, 0.88),
+    },
+    head: rgb(0.65, 0.9, 0.7),
+    body: [rgb(0.55, 0.85, 0.75), rgb(0.7, 0.75, 0.95)],
+    eye: grey(1.0),
+    pupil: rgb(0.3, 0.25, 0.4),
+    food: [rgb(1.0, 0.55, 0.6), rgb(1.0, 0.85, 0.5), rgb(0.75, 0.6, 1.0)],
+    ui: UiPalette {
+        title: rgb(0.5, 0.4, 0.7),
+        item: rgb(0.35, 0.3, 0.45),
+        selected: rgb(0.9, 0.45, 0.55),
+        panel: rgb(0.95, 0.93, 0.98),
+    },
+};
+
+// as far apart as possible, nothing relies on telling two colors of similar brightness apart
+pub static HIGH_CONTRAST: Theme = Theme {
+    name: "HIGH CONTRAST",
+    background: Color32::new(0, 0, 0, 255),
+    floor: FloorPalette {
+        empty: grey(0.2),
+        occupied: grey(0.05),
+        food: [grey(0.05), rgb(0.5, 0.5, 0.0)],
+        trail: grey(0.4),
+        danger: rgb(1.0, 0.0, 0.0),
+    },
+    dressing: DressingPalette {
+        portal: [grey(0.4), grey(1.0)],
+        grid: grey(0.0),
+        corner: grey(1.0),
+        checker: grey(1.0),
+        gradient: [grey(1.0), grey(1.0)],
+    },
+    backdrop: BackdropPalette {
+        star: grey(0.5),
+        nebula: [grey(0.02), grey(0.06)],
+        debris: grey(0.15),
+    },
+    head: grey(1.0),
+    body: [rgb(1.0, 1.0, 0.0), rgb(1.0, 1.0, 0.0)],
+    eye: grey(0.0),
+    pupil: grey(1.0),
+    food: [rgb(0.0, 1.0, 1.0), rgb(1.0, 0.0, 1.0), rgb(0.0, 1.0, 0.0)],
+    ui: UiPalette {
+        title: rgb(1.0, 1.0, 0.0),
+        item: grey(1.0),
+        selected: rgb(0.0, 1.0, 1.0),
+        panel: grey(0.0),
+    },
+};
+
+// the Okabe-Ito colors, the snake is blue and everything it has to notice is orange or yellow, never red against green
+pub static COLOR_BLIND: Theme = Theme {
+    name: "COLOR BLIND SAFE",
+    background: Color32::new(0, 0, 0, 255),
+    floor: FloorPalette {
+        empty: grey(0.25),
+        occupied: grey(0.15),
+        food: [grey(0.15), rgb(0.5, 0.35, 0.0)],
+        trail: rgb(0.15, 0.3, 0.45),
+        danger: rgb(0.84, 0.37, 0.0),
+    },
+    dressing: DressingPalette {
+        portal: [grey(0.2), rgb(0.94, 0.89, 0.26)],
+        grid: grey(0.1),
+        corner: grey(0.9),
+        checker: grey(0.85),
+        gradient: [grey(1.0), grey(0.8)],
+    },
+    backdrop: BackdropPalette {
+        star: grey(0.9),
+        nebula: [rgb(0.02, 0.04, 0.08), rgb(0.05, 0.12, 0.2)],
+        debris: grey(0.3),
+    },
+    head: rgb(0.34, 0.71, 0.91),
+    body: [rgb(0.0, 0.45, 0.7), rgb(0.0, 0.3, 0.5)],
+    eye: grey(1.0),
+    pupil: grey(0.0),
+    food: [rgb(0.9, 0.62, 0.0), rgb(0.94, 0.89, 0.26), rgb(0.8, 0.47, 0.65)],
+    ui: UiPalette {
+        title: rgb(0.34, 0.71, 0.91),
+        item: grey(0.8),
+        selected: rgb(0.9, 0.62, 0.0),
+        panel: grey(0.05),
+    },
+};
+
+pub static THEMES: [&Theme; 5] = [&CLASSIC, &NEON, &PASTEL, &HIGH_CONTRAST, &COLOR_BLIND];
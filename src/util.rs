@@ -1,6 +1,6 @@
-use dbsdk_rs::{math::{Vector3, Vector4}, gamepad::{GamepadState, GamepadButton}};
+use dbsdk_rs::{math::{Vector3, Vector4, Quaternion, Matrix4x4}};
 
-use crate::{rng::Rng, snake::Direction};
+use crate::rng::Rng;
 
 // built in min/max uses Ord which f32 doesn't satisfy
 pub fn min(a: f32, b: f32) -> f32 {
@@ -24,9 +24,9 @@ pub fn vec3_to4(v: Vector3) -> Vector4 {
 
 pub fn vec3_rand(rng: &mut Rng, min: i16, max: i16) -> Vector3 {
     vec3(
-        (rng.random_single((max - min).abs() as u16) as i16 + min) as f32,
-        (rng.random_single((max - min).abs() as u16) as i16 + min) as f32,
-        (rng.random_single((max - min).abs() as u16) as i16 + min) as f32
+        (rng.random_single((max - min).unsigned_abs()) as i16 + min) as f32,
+        (rng.random_single((max - min).unsigned_abs()) as i16 + min) as f32,
+        (rng.random_single((max - min).unsigned_abs()) as i16 + min) as f32
     )
 }
 
@@ -34,39 +34,22 @@ pub fn vec4(x: f32, y: f32, z: f32, w: f32) -> Vector4 {
     Vector4::new(x, y, z, w)
 }
 
-pub fn read_deadzone(deadzone: f32, x: i16, y: i16) -> Option<Direction> {
-    let x = (x as f32) / (i16::MAX as f32);
-    let y = (y as f32) / (i16::MAX as f32);
-
-    if x.abs() > deadzone || y.abs() > deadzone {
-        if x.abs() > y.abs() {
-            if x > 0.0 {
-                Some(Direction::Right)
-            } else {
-                Some(Direction::Left)
-            }
-        } else {
-            if y > 0.0 {
-                Some(Direction::Up)
-            } else {
-                Some(Direction::Down)
-            }
-        }
-    } else {
-        None
-    }
+// euler angles in degrees to a normalized quaternion
+pub fn quat_euler(degrees: Vector3) -> Quaternion {
+    Quaternion::from_euler(vec3(
+        degrees.x.to_radians(),
+        degrees.y.to_radians(),
+        degrees.z.to_radians()
+    )).normalized()
 }
 
-pub fn read_gamepad(state: GamepadState) -> Option<Direction> {
-    if state.button_mask.contains(GamepadButton::Up) {
-        Some(Direction::Up)
-    } else if state.button_mask.contains(GamepadButton::Down) {
-        Some(Direction::Down)
-    } else if state.button_mask.contains(GamepadButton::Left) {
-        Some(Direction::Left)
-    } else if state.button_mask.contains(GamepadButton::Right) {
-        Some(Direction::Right)
-    } else {
-        None
+// a * b, same order as Matrix4x4::mul_simd applies them
+pub fn mat_mul(a: &Matrix4x4, b: &Matrix4x4) -> Matrix4x4 {
+    let mut m = Matrix4x4::identity();
+    for i in 0..4 {
+        for j in 0..4 {
+            m.m[i][j] = a.m[i][0] * b.m[0][j] + a.m[i][1] * b.m[1][j] + a.m[i][2] * b.m[2][j] + a.m[i][3] * b.m[3][j];
+        }
     }
+    m
 }
@@ -0,0 +1,87 @@
+/*
+renders a few fixed scenes with the software rasterizer and compares them against images/golden
+    cargo test --test golden --target x86_64-unknown-linux-gnu
+set BLESS=1 to write the current output as the new reference images
+*/
+extern crate dbsdk_rs;
+extern crate snake;
+
+use std::env;
+use std::fs::{self, File};
+use std::path::Path;
+
+use dbsdk_rs::vdp::{Color32, Compare};
+
+use snake::raster::image;
+use snake::render::Renderer;
+use snake::render::software::SoftwareRenderer;
+use snake::rng::Rng;
+use snake::snake::Game;
+
+// same board as the console, fixed seeds so food always lands in the same place
+fn game() -> Game {
+    let mut game = Game::with_rng(12, 12, 3, 4, 8, Rng::from_seeds([3, 7]));
+    for _ in 0..300 {
+        game.tick();
+    }
+    game
+}
+
+fn render(transform: bool) -> SoftwareRenderer {
+    let mut renderer = SoftwareRenderer::new(640, 480);
+    renderer.set_depth(true, Compare::LessOrEqual);
+
+    let mut game = game();
+    if transform {
+        game.draw(&mut renderer);
+    } else {
+        // skip the camera so the raw board tris show up in clip space
+        let (mut tris, _) = game.scene();
+        renderer.clear(Color32::new(0, 0, 0, 255), 1.0);
+        renderer.set_camera(&[]);
+        renderer.submit(&mut tris);
+    }
+
+    renderer
+}
+
+// writes what was rendered to target/golden either way, so a mismatch can be looked at
+fn check(name: &str, renderer: &SoftwareRenderer) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let out_dir = root.join("target/golden");
+    fs::create_dir_all(&out_dir).expect("couldn't create target/golden");
+
+    let raster = &renderer.raster;
+    let mut png = Vec::new();
+    image::write_png(raster, &mut png).unwrap();
+
+    let out = out_dir.join(format!("{}.png", name));
+    fs::write(&out, &png).unwrap();
+    image::write_ppm(raster, &mut File::create(out_dir.join(format!("{}.ppm", name))).unwrap()).unwrap();
+
+    let reference = root.join("images/golden").join(format!("{}.png", name));
+    if env::var_os("BLESS").is_some() {
+        fs::write(&reference, &png).unwrap();
+        return
+    }
+
+    // pixels rather than bytes, so a change to the png encoder doesn't invalidate every reference
+    let expected = fs::read(&reference).ok().and_then(|bytes| image::read_png(&bytes));
+    let (width, height, pixels) = match expected {
+        Some(decoded) => decoded,
+        None => panic!("couldn't read {} (run with BLESS=1 to create it)", reference.display()),
+    };
+    let same = width == raster.width && height == raster.height
+        && pixels.iter().zip(raster.pixels()).all(|(p, c)| *p == [c.r, c.g, c.b, c.a]);
+    assert!(same, "mismatch {} (see {})", reference.display(), out.display());
+}
+
+#[test]
+fn board() {
+    check("board", &render(true));
+}
+
+#[test]
+fn no_camera_transform() {
+    check("no_camera_transform", &render(false));
+}